@@ -0,0 +1,311 @@
+// `#[derive(FromPattern)]` implements FromStr from a pattern string, for input lines
+// that are mostly fixed text with a few values in between.
+//
+//     #[derive(FromPattern)]
+//     #[pattern("Game {id}: {draws:; }")]
+//     struct Game {
+//         id: u32,
+//         draws: Vec<Draw>,
+//     }
+//
+// Every `{field}` is parsed with the FromStr of its type, tuple fields are named by
+// index like `{0}`. `{field:sep}` splits the text on `sep` and collects the parts into a
+// collection such as Vec<T>. The text between placeholders has to match exactly, a
+// placeholder takes whatever makes the rest of the line match, so `{name}-{id}` finds
+// the dash before the id even when the name contains dashes.
+//
+// Enums put a pattern on every variant, the first variant that matches wins.
+//
+// The error is a String, so the Err of every field type has to implement Display.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Fields, GenericArgument,
+    LitStr, PathArguments, Type,
+};
+
+#[proc_macro_derive(FromPattern, attributes(pattern))]
+pub fn derive_from_pattern(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern_of(&input.attrs, input.ident.span())?;
+            matcher(&pattern, &data.fields, quote!(Self))?
+        }
+        Data::Enum(data) => {
+            let mut attempts = Vec::new();
+            for variant in &data.variants {
+                let pattern = pattern_of(&variant.attrs, variant.ident.span())?;
+                let ident = &variant.ident;
+                let matcher = matcher(&pattern, &variant.fields, quote!(Self::#ident))?;
+                attempts.push(quote! {
+                    let attempt = |input: &str| -> ::std::result::Result<Self, String> {
+                        #matcher
+                    };
+                    match attempt(input) {
+                        Ok(value) => return Ok(value),
+                        Err(error) => errors.push(error),
+                    }
+                });
+            }
+            let name = name.to_string();
+            quote! {
+                let mut errors: Vec<String> = Vec::new();
+                #(#attempts)*
+                Err(format!(
+                    "{:?} is not a {}, {}",
+                    input,
+                    #name,
+                    errors.join(", ")
+                ))
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "FromPattern can't be derived for unions",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = String;
+
+            fn from_str(input: &str) -> ::std::result::Result<Self, String> {
+                #body
+            }
+        }
+    })
+}
+
+fn pattern_of(attrs: &[Attribute], span: Span) -> syn::Result<LitStr> {
+    let mut patterns = attrs.iter().filter(|attr| attr.path().is_ident("pattern"));
+    let attr = patterns
+        .next()
+        .ok_or_else(|| syn::Error::new(span, "missing #[pattern(\"...\")]"))?;
+    if let Some(extra) = patterns.next() {
+        return Err(syn::Error::new(extra.span(), "only one pattern is allowed"));
+    }
+    attr.parse_args()
+}
+
+// A pattern split up as the text before the first placeholder, then every placeholder
+// with the text that follows it
+struct Pattern {
+    prefix: String,
+    placeholders: Vec<Placeholder>,
+}
+
+struct Placeholder {
+    field: String,
+    separator: Option<String>,
+    suffix: String,
+}
+
+fn split_pattern(lit: &LitStr) -> syn::Result<Pattern> {
+    let error = |message: &str| syn::Error::new(lit.span(), message);
+    let text = lit.value();
+    let mut rest = text.as_str();
+
+    let start = rest.find('{').unwrap_or(rest.len());
+    let prefix = rest[..start].to_string();
+    rest = &rest[start..];
+
+    let mut placeholders = Vec::new();
+    while let Some(inner) = rest.strip_prefix('{') {
+        let end = inner.find('}').ok_or_else(|| error("unclosed `{`"))?;
+        let (field, separator) = match inner[..end].split_once(':') {
+            Some((field, separator)) if !separator.is_empty() => {
+                (field, Some(separator.to_string()))
+            }
+            Some(_) => return Err(error("empty separator")),
+            None => (&inner[..end], None),
+        };
+        rest = &inner[end + 1..];
+
+        let next = rest.find('{').unwrap_or(rest.len());
+        let suffix = rest[..next].to_string();
+        rest = &rest[next..];
+        if suffix.is_empty() && !rest.is_empty() {
+            return Err(error(&format!(
+                "`{{{field}}}` needs some text between it and the next placeholder"
+            )));
+        }
+        placeholders.push(Placeholder {
+            field: field.trim().to_string(),
+            separator,
+            suffix,
+        });
+    }
+    Ok(Pattern {
+        prefix,
+        placeholders,
+    })
+}
+
+// The element type of a collection like Vec<T>, for fields with a separator
+fn element_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+// The body of a function that takes `input: &str` and returns Result<Self, String>,
+// building the value with `constructor`
+fn matcher(lit: &LitStr, fields: &Fields, constructor: TokenStream2) -> syn::Result<TokenStream2> {
+    let pattern = split_pattern(lit)?;
+
+    // Which field every placeholder fills, in the order of the pattern
+    let names = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => index.to_string(),
+        })
+        .collect::<Vec<_>>();
+    let mut order = Vec::new();
+    for placeholder in &pattern.placeholders {
+        let index = names
+            .iter()
+            .position(|name| *name == placeholder.field)
+            .ok_or_else(|| {
+                syn::Error::new(
+                    lit.span(),
+                    format!("there is no field `{}`", placeholder.field),
+                )
+            })?;
+        if order.contains(&index) {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("`{}` is used twice", placeholder.field),
+            ));
+        }
+        order.push(index);
+    }
+    if let Some(missing) = (0..names.len()).find(|index| !order.contains(index)) {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!("the pattern doesn't fill `{}`", names[missing]),
+        ));
+    }
+
+    let values = (0..names.len())
+        .map(|index| format_ident!("value_{}", index))
+        .collect::<Vec<_>>();
+    let build = match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote!(#constructor { #(#idents: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#constructor(#(#values),*)),
+        Fields::Unit => quote!(#constructor),
+    };
+
+    // Built from the last placeholder outwards, each one wraps the ones after it. The
+    // placeholders before the last try every place their suffix shows up and move on
+    // to the next one when a value doesn't parse.
+    let mut inner = quote!(return Ok(#build););
+    for (position, placeholder) in pattern.placeholders.iter().enumerate().rev() {
+        let index = order[position];
+        let ty = &fields.iter().nth(index).expect("Every index is a field").ty;
+        let value = &values[index];
+        let name = &placeholder.field;
+        let suffix = &placeholder.suffix;
+        let last = position + 1 == pattern.placeholders.len();
+
+        let parse = match &placeholder.separator {
+            None => quote!(text.parse::<#ty>()),
+            Some(separator) => {
+                let element = element_type(ty).ok_or_else(|| {
+                    syn::Error::new(
+                        ty.span(),
+                        "a field with a separator needs a collection type like Vec<T>",
+                    )
+                })?;
+                quote! {
+                    text.split(#separator)
+                        .map(|part| part.parse::<#element>())
+                        .collect::<::std::result::Result<#ty, _>>()
+                }
+            }
+        };
+        let give_up = if last {
+            quote!(break 'last)
+        } else {
+            quote!(continue)
+        };
+        let parse_value = quote! {
+            let #value = match #parse {
+                Ok(value) => value,
+                Err(cause) => {
+                    error = Some(format!("`{}` can't be {:?}: {}", #name, text, cause));
+                    #give_up;
+                }
+            };
+        };
+
+        inner = if last {
+            // The last placeholder runs up to the text that ends the line
+            quote! {
+                if let Some(text) = rest.strip_suffix(#suffix) {
+                    'last: {
+                        #parse_value
+                        #inner
+                    }
+                } else {
+                    error = Some(format!("expected {:?} at the end of {:?}", #suffix, rest));
+                }
+            }
+        } else {
+            quote! {
+                if !rest.contains(#suffix) {
+                    error = Some(format!("expected {:?} after `{}` in {:?}", #suffix, #name, rest));
+                }
+                for (at, _) in rest.match_indices(#suffix) {
+                    let text = &rest[..at];
+                    let rest = &rest[at + #suffix.len()..];
+                    #parse_value
+                    #inner
+                }
+            }
+        };
+    }
+    if pattern.placeholders.is_empty() {
+        inner = quote! {
+            if rest.is_empty() {
+                return Ok(#build);
+            }
+            error = Some(format!("unexpected {:?} at the end", rest));
+        };
+    }
+
+    let prefix = &pattern.prefix;
+    Ok(quote! {
+        let mut error: Option<String> = None;
+        let Some(rest) = input.strip_prefix(#prefix) else {
+            return Err(format!("expected {:?} at the start of {:?}", #prefix, input));
+        };
+        #inner
+        Err(error.unwrap_or_else(|| format!("{:?} doesn't match {:?}", input, #lit)))
+    })
+}
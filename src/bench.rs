@@ -0,0 +1,369 @@
+#![allow(unused)]
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    process::{Command, ExitCode},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use itertools::Itertools;
+
+use crate::solutions::{self, Solver};
+
+const HISTORY_FILE: &str = "bench_history.csv";
+
+#[derive(Debug, PartialEq, Clone)]
+struct BenchRecord {
+    commit: String,
+    date: String,
+    year: u32,
+    day: u32,
+    part: u32,
+    nanos: u128,
+}
+
+impl BenchRecord {
+    fn to_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.commit, self.date, self.year, self.day, self.part, self.nanos
+        )
+    }
+
+    fn parse_line(line: &str) -> Option<BenchRecord> {
+        let (commit, date, year, day, part, nanos) = line.split(',').collect_tuple()?;
+        Some(BenchRecord {
+            commit: commit.to_string(),
+            date: date.to_string(),
+            year: year.parse().ok()?,
+            day: day.parse().ok()?,
+            part: part.parse().ok()?,
+            nanos: nanos.parse().ok()?,
+        })
+    }
+
+    fn same_part(&self, other: &BenchRecord) -> bool {
+        (self.year, self.day, self.part) == (other.year, other.day, other.part)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct BenchOptions {
+    days: Vec<(u32, u32)>,
+    compare: bool,
+    baseline: Option<String>,
+    threshold: f64,
+    runs: u32,
+}
+
+fn parse_options(args: &[String]) -> Result<BenchOptions, String> {
+    let mut positional = Vec::new();
+    let mut options = BenchOptions {
+        days: Vec::new(),
+        compare: false,
+        baseline: None,
+        threshold: 10.0,
+        runs: 5,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} needs a value"))
+                .cloned()
+        };
+        match arg.as_str() {
+            "--compare" => options.compare = true,
+            "--baseline" => {
+                options.compare = true;
+                options.baseline = Some(value("--baseline")?);
+            }
+            "--threshold" => {
+                options.threshold = value("--threshold")?
+                    .parse()
+                    .map_err(|_| "--threshold must be a percentage".to_string())?
+            }
+            "--runs" => {
+                options.runs = value("--runs")?
+                    .parse()
+                    .ok()
+                    .filter(|&runs| runs > 0)
+                    .ok_or_else(|| "--runs must be a positive number".to_string())?
+            }
+            _ => positional.push(
+                arg.parse::<u32>()
+                    .map_err(|_| format!("Unknown argument: {arg}"))?,
+            ),
+        }
+    }
+
+    options.days = match positional[..] {
+        [] => solutions::DAYS.to_vec(),
+        [year] => solutions::DAYS
+            .iter()
+            .filter(|(y, _)| *y == year)
+            .copied()
+            .collect(),
+        [year, day] => vec![(year, day)],
+        _ => return Err("Expected at most a year and a day".to_string()),
+    };
+
+    Ok(options)
+}
+
+// Median of several runs, so a single hiccup doesn't count as a regression
+fn measure(solver: Solver, input: &str, runs: u32) -> Duration {
+    let mut times = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            std::hint::black_box(solver(input));
+            start.elapsed()
+        })
+        .collect_vec();
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn current_commit() -> String {
+    Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// Formats days since the unix epoch as YYYY-MM-DD (proleptic gregorian calendar)
+fn format_date(days_since_epoch: i64) -> String {
+    let z = days_since_epoch + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format_date((secs / 86_400) as i64)
+}
+
+fn load_history() -> Vec<BenchRecord> {
+    fs::read_to_string(HISTORY_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(BenchRecord::parse_line)
+        .collect()
+}
+
+fn append_history(records: &[BenchRecord]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)?;
+    records
+        .iter()
+        .try_for_each(|record| writeln!(file, "{}", record.to_line()))
+}
+
+// The latest earlier measurement of the same part, either from the chosen baseline
+// commit or from the last commit that isn't the current one
+fn find_baseline<'a>(
+    history: &'a [BenchRecord],
+    current: &BenchRecord,
+    baseline: Option<&str>,
+) -> Option<&'a BenchRecord> {
+    history.iter().rev().find(|old| {
+        old.same_part(current)
+            && match baseline {
+                Some(commit) => old.commit.starts_with(commit),
+                None => old.commit != current.commit,
+            }
+    })
+}
+
+// How many times faster the new measurement is, below 1.0 means slower
+fn speedup(old: &BenchRecord, new: &BenchRecord) -> f64 {
+    old.nanos as f64 / new.nanos.max(1) as f64
+}
+
+fn is_regression(old: &BenchRecord, new: &BenchRecord, threshold_percent: f64) -> bool {
+    new.nanos as f64 > old.nanos as f64 * (1.0 + threshold_percent / 100.0)
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let options = match parse_options(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let history = load_history();
+    let commit = current_commit();
+    let date = today();
+    let mut records = Vec::new();
+
+    for &(year, day) in &options.days {
+        let Some((part1, part2)) = solutions::solvers(year, day) else {
+            eprintln!("No solution for {year} day {day}");
+            return ExitCode::FAILURE;
+        };
+        let Ok(input) = crate::read_input(year, day) else {
+            eprintln!("Skipping {year} day {day}, no input file");
+            continue;
+        };
+
+        for (part, solver) in [(1, part1), (2, part2)] {
+            let elapsed = measure(solver, &input, options.runs);
+            println!("{year} day{day} part{part}: {elapsed:?}");
+            records.push(BenchRecord {
+                commit: commit.clone(),
+                date: date.clone(),
+                year,
+                day,
+                part,
+                nanos: elapsed.as_nanos(),
+            });
+        }
+    }
+
+    if let Err(err) = append_history(&records) {
+        eprintln!("Could not write {HISTORY_FILE}: {err}");
+    }
+
+    if !options.compare {
+        return ExitCode::SUCCESS;
+    }
+
+    println!();
+    let mut regressions = 0;
+    for new in &records {
+        let Some(old) = find_baseline(&history, new, options.baseline.as_deref()) else {
+            println!("{} day{} part{}: no baseline", new.year, new.day, new.part);
+            continue;
+        };
+
+        let ratio = speedup(old, new);
+        let verdict = if is_regression(old, new, options.threshold) {
+            regressions += 1;
+            "REGRESSION"
+        } else {
+            ""
+        };
+        println!(
+            "{} day{} part{}: {:?} ({}) -> {:?} ({:.2}x) {verdict}",
+            new.year,
+            new.day,
+            new.part,
+            Duration::from_nanos(old.nanos as u64),
+            old.commit,
+            Duration::from_nanos(new.nanos as u64),
+            ratio,
+        );
+    }
+
+    if regressions > 0 {
+        eprintln!(
+            "{regressions} part(s) regressed by more than {}%",
+            options.threshold
+        );
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod test_bench {
+    use super::*;
+
+    fn record(commit: &str, part: u32, nanos: u128) -> BenchRecord {
+        BenchRecord {
+            commit: commit.to_string(),
+            date: "2023-12-05".to_string(),
+            year: 2023,
+            day: 5,
+            part,
+            nanos,
+        }
+    }
+
+    fn args(input: &str) -> Vec<String> {
+        input.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_record_roundtrip() {
+        let rec = record("abc123", 2, 1500);
+        assert_eq!("abc123,2023-12-05,2023,5,2,1500", rec.to_line());
+        assert_eq!(
+            Some(rec),
+            BenchRecord::parse_line("abc123,2023-12-05,2023,5,2,1500")
+        );
+        assert_eq!(None, BenchRecord::parse_line("abc123,2023-12-05,2023,5"));
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!("1970-01-01", format_date(0));
+        assert_eq!("2000-02-29", format_date(11_016));
+        assert_eq!("2023-12-05", format_date(19_696));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse_options(&args("2023 5 --compare --threshold 25")).unwrap();
+        assert_eq!(vec![(2023, 5)], options.days);
+        assert!(options.compare);
+        assert_eq!(25.0, options.threshold);
+
+        let options = parse_options(&args("2016 --baseline abc123")).unwrap();
+        assert_eq!(10, options.days.len());
+        assert_eq!(Some("abc123".to_string()), options.baseline);
+
+        assert!(parse_options(&args("--runs 0")).is_err());
+        assert!(parse_options(&args("--fast")).is_err());
+    }
+
+    #[test]
+    fn test_find_baseline() {
+        let history = vec![
+            record("first", 2, 1000),
+            record("second", 1, 10),
+            record("second", 2, 800),
+            record("current", 2, 700),
+        ];
+        let new = record("current", 2, 400);
+
+        assert_eq!(Some(&history[2]), find_baseline(&history, &new, None));
+        assert_eq!(
+            Some(&history[0]),
+            find_baseline(&history, &new, Some("fir"))
+        );
+        assert_eq!(None, find_baseline(&history, &new, Some("missing")));
+    }
+
+    #[test]
+    fn test_regression() {
+        let old = record("old", 1, 1000);
+
+        assert_eq!(2.0, speedup(&old, &record("new", 1, 500)));
+        assert!(!is_regression(&old, &record("new", 1, 1050), 10.0));
+        assert!(is_regression(&old, &record("new", 1, 1200), 10.0));
+    }
+}
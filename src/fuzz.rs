@@ -0,0 +1,250 @@
+#![allow(unused)]
+
+// Fuzzing for the day solutions. Inputs are generated inputs that get mangled byte by
+// byte, plus plain random bytes, and every input is fed through parsing and solving of
+// both parts. The solutions take a &str, so only inputs that are valid UTF-8 are run,
+// the rest are skipped. A panic (including arithmetic overflow) or a run that doesn't
+// finish in time counts as a crash. Overflow only panics with overflow checks on, so the
+// command refuses to run without them: use a debug build or `--profile fuzz`.
+
+use std::{fmt::Display, process::ExitCode, sync::mpsc, thread, time::Duration};
+
+use crate::{
+    generate, solutions,
+    utils::{prop, rng::Rng},
+};
+
+const TIME_LIMIT: Duration = Duration::from_secs(2);
+
+// Fragments that tend to break parsers: huge and negative numbers, odd line endings,
+// multi byte characters and stray brackets
+const INTERESTING: &[&str] = &[
+    "0",
+    "-1",
+    "4294967296",
+    "18446744073709551616",
+    "\n",
+    "\r\n",
+    "\n\n",
+    " ",
+    "é",
+    "🎄",
+    "(",
+    ")",
+    "[",
+    "]",
+    "x",
+    ":",
+    ",",
+    "=",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Failure {
+    Panicked(String),
+    TimedOut,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Panicked(message) => write!(f, "panicked: {message}"),
+            Failure::TimedOut => write!(f, "did not finish within {TIME_LIMIT:?}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Crash {
+    pub input: Vec<u8>,
+    pub failure: Failure,
+}
+
+// The entry point for one day: parses and solves both parts of the input. Input that
+// isn't UTF-8 can't reach a solution and passes.
+pub fn fuzz_day(year: u32, day: u32, data: &[u8]) -> Result<(), Failure> {
+    let (part1, part2) =
+        solutions::solvers(year, day).expect("Only days with solutions are fuzzed");
+    let Ok(input) = std::str::from_utf8(data).map(str::to_string) else {
+        return Ok(());
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = prop::catch_panic(|| {
+            part1(&input);
+            part2(&input);
+        });
+        sender.send(result).ok();
+    });
+
+    match receiver.recv_timeout(TIME_LIMIT) {
+        Ok(result) => result.map_err(Failure::Panicked),
+        // The solver thread is left running, there is no way to stop it
+        Err(_) => Err(Failure::TimedOut),
+    }
+}
+
+fn mutate(rng: &mut Rng, mut data: Vec<u8>) -> Vec<u8> {
+    for _ in 0..=rng.below(4) {
+        let at = rng.index(data.len() + 1);
+        match rng.below(6) {
+            0 if at < data.len() => data[at] = rng.below(256) as u8,
+            1 => data.insert(at, rng.below(256) as u8),
+            2 => {
+                let end = (at + rng.index(8) + 1).min(data.len());
+                data.drain(at..end);
+            }
+            3 => {
+                let end = (at + rng.index(16) + 1).min(data.len());
+                let copy = data[at..end].to_vec();
+                data.splice(at..at, copy);
+            }
+            4 => data.truncate(at),
+            _ => {
+                let fragment = rng.pick(INTERESTING).as_bytes().to_vec();
+                data.splice(at..at, fragment);
+            }
+        }
+    }
+    data
+}
+
+fn random_input(rng: &mut Rng, year: u32, day: u32) -> Vec<u8> {
+    match generate::generator(year, day) {
+        Some(generate) if rng.chance(9, 10) => {
+            let size = rng.range(1..20) as usize;
+            let valid = generate(rng, size).into_bytes();
+            mutate(rng, valid)
+        }
+        _ => (0..rng.below(64)).map(|_| rng.below(256) as u8).collect(),
+    }
+}
+
+// Runs the day on `iterations` inputs and returns the first crash. Panics are shrunk to a
+// small input, timeouts are reported as they are since every attempt takes the full limit.
+pub fn find_crash(year: u32, day: u32, seed: u64, iterations: u32) -> Option<Crash> {
+    let mut rng = Rng::new(seed);
+    let panics = |input: &Vec<u8>| matches!(fuzz_day(year, day, input), Err(Failure::Panicked(_)));
+
+    (0..iterations).find_map(|_| {
+        let input = random_input(&mut rng, year, day);
+        match fuzz_day(year, day, &input) {
+            Ok(()) => None,
+            Err(Failure::TimedOut) => Some(Crash {
+                input,
+                failure: Failure::TimedOut,
+            }),
+            Err(Failure::Panicked(_)) => {
+                let input = prop::minimize(input, panics);
+                let failure = fuzz_day(year, day, &input).unwrap_err();
+                Some(Crash { input, failure })
+            }
+        }
+    })
+}
+
+// Whether arithmetic overflow panics in this build
+fn overflow_checks() -> bool {
+    prop::catch_panic(|| std::hint::black_box(u8::MAX) + 1).is_err()
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let usage = "Usage: fuzz [<year> [<day>]] [--iterations <n>] [--seed <s>]\n\
+                 Only inputs that are valid UTF-8 are run. Build with overflow checks, \
+                 like `cargo run --profile fuzz -- fuzz`.";
+    let mut positional = Vec::new();
+    let mut iterations = 1000;
+    let mut seed = 0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--iterations" => args
+                .next()
+                .and_then(|v| v.parse().ok())
+                .map(|v| iterations = v),
+            "--seed" => args.next().and_then(|v| v.parse().ok()).map(|v| seed = v),
+            _ => arg.parse::<u32>().ok().map(|v| positional.push(v)),
+        };
+        if parsed.is_none() {
+            eprintln!("{usage}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if !overflow_checks() {
+        eprintln!("Overflow checks are off, so overflows would go unnoticed\n{usage}");
+        return ExitCode::FAILURE;
+    }
+
+    let days = solutions::DAYS
+        .iter()
+        .copied()
+        .filter(|&(year, day)| match positional[..] {
+            [] => true,
+            [y] => year == y,
+            [y, d] => (year, day) == (y, d),
+            _ => false,
+        });
+
+    let mut crashes = 0;
+    for (year, day) in days {
+        match find_crash(year, day, seed, iterations) {
+            None => println!("{year} day{day}: no crashes in {iterations} inputs"),
+            Some(crash) => {
+                crashes += 1;
+                println!(
+                    "{year} day{day}: {}\n    input: {:?}",
+                    crash.failure,
+                    String::from_utf8_lossy(&crash.input)
+                );
+            }
+        }
+    }
+
+    if crashes > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod test_fuzz {
+    use super::*;
+
+    #[test]
+    fn test_fuzz_day_returns_errors() {
+        // Every line in 2023 day 2 has to be a game, which is an error and not a panic
+        assert_eq!(Ok(()), fuzz_day(2023, 2, b"not a game"));
+        assert_eq!(Ok(()), fuzz_day(2023, 2, b"Game 1: 3 blue"));
+        assert_eq!(Ok(()), fuzz_day(2023, 2, &[0xff, 0xfe]));
+        assert_eq!(Ok(()), fuzz_day(2023, 9, "¡".as_bytes()));
+        assert_eq!(Ok(()), fuzz_day(2023, 11, b"\n\0"));
+    }
+
+    #[test]
+    fn test_mutate_is_seeded() {
+        let input = b"rect 3x2\nrotate row y=0 by 4".to_vec();
+        assert_eq!(
+            mutate(&mut Rng::new(9), input.clone()),
+            mutate(&mut Rng::new(9), input)
+        );
+    }
+
+    #[test]
+    fn test_overflow_checks() {
+        // Tests are built with them
+        assert!(overflow_checks());
+    }
+
+    #[test]
+    fn test_no_day_crashes() {
+        // Mining the 2016 day 5 password takes millions of hashes for any door id, which
+        // is always longer than the time limit
+        let days = solutions::DAYS.iter().filter(|&&day| day != (2016, 5));
+        for &(year, day) in days {
+            assert_eq!(None, find_crash(year, day, 1, 200), "{year} day {day}");
+        }
+    }
+}
@@ -0,0 +1,630 @@
+#![allow(unused)]
+
+// Generators for synthetic puzzle inputs of any size, used to stress test how the
+// solutions scale. The meaning of `size` depends on the day and is noted on each
+// generator; the output is the same for the same seed.
+
+use std::{cmp::Ordering, ops::Range, process::ExitCode};
+
+use itertools::Itertools;
+
+use crate::utils::rng::Rng;
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+fn letters(rng: &mut Rng, len: usize, alphabet: &[u8]) -> String {
+    (0..len).map(|_| *rng.pick(alphabet) as char).collect()
+}
+
+fn random_letters(rng: &mut Rng, lengths: Range<i64>, alphabet: &[u8]) -> String {
+    let len = rng.range(lengths) as usize;
+    letters(rng, len, alphabet)
+}
+
+pub fn generator(year: u32, day: u32) -> Option<fn(&mut Rng, usize) -> String> {
+    Some(match (year, day) {
+        (2016, 1) => year2016_day1,
+        (2016, 2) => year2016_day2,
+        (2016, 3) => year2016_day3,
+        (2016, 4) => year2016_day4,
+        (2016, 5) => year2016_day5,
+        (2016, 6) => year2016_day6,
+        (2016, 7) => year2016_day7,
+        (2016, 8) => year2016_day8,
+        (2016, 9) => year2016_day9,
+        (2016, 10) => year2016_day10,
+        (2023, 1) => year2023_day1,
+        (2023, 2) => year2023_day2,
+        (2023, 3) => year2023_day3,
+        (2023, 4) => year2023_day4,
+        (2023, 5) => year2023_day5,
+        (2023, 6) => year2023_day6,
+        (2023, 7) => year2023_day7,
+        (2023, 8) => year2023_day8,
+        (2023, 9) => year2023_day9,
+        (2023, 10) => year2023_day10,
+        (2023, 11) => year2023_day11,
+        _ => return None,
+    })
+}
+
+// size: number of turn instructions
+fn year2016_day1(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}{}", rng.pick(&['L', 'R']), rng.range(1..200)))
+        .join(", ")
+}
+
+// size: number of lines
+fn year2016_day2(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| random_letters(rng, 1..100, b"UDLR"))
+        .join("\n")
+}
+
+// size: number of lines, rounded up to whole groups of three
+fn year2016_day3(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1).div_ceil(3) * 3)
+        .map(|_| {
+            (0..3)
+                .map(|_| format!("{:>5}", rng.range(1..1000)))
+                .join("")
+        })
+        .join("\n")
+}
+
+// size: number of rooms, about half of them real
+fn year2016_day4(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let groups = (0..rng.range(1..6))
+                .map(|_| random_letters(rng, 1..10, LOWERCASE))
+                .collect_vec();
+            let check_sum = if rng.chance(1, 2) {
+                groups
+                    .concat()
+                    .chars()
+                    .counts()
+                    .into_iter()
+                    .sorted_by(|(c1, n1), (c2, n2)| n2.cmp(n1).then(c1.cmp(c2)))
+                    .map(|(c, _)| c)
+                    .take(5)
+                    .collect()
+            } else {
+                letters(rng, 5, LOWERCASE)
+            };
+            format!("{}-{}[{check_sum}]", groups.join("-"), rng.range(100..1000))
+        })
+        .join("\n")
+}
+
+// size: unused, the work depends on how soon the hashes turn up
+fn year2016_day5(rng: &mut Rng, _size: usize) -> String {
+    letters(rng, 8, LOWERCASE)
+}
+
+// size: number of messages
+fn year2016_day6(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| letters(rng, 8, LOWERCASE))
+        .join("\n")
+}
+
+// size: number of addresses
+fn year2016_day7(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let supernets = (0..rng.range(2..5))
+                .map(|_| random_letters(rng, 3..10, b"abcd"))
+                .collect_vec();
+            supernets
+                .into_iter()
+                .reduce(|address, supernet| {
+                    let hypernet = random_letters(rng, 3..10, b"abcd");
+                    format!("{address}[{hypernet}]{supernet}")
+                })
+                .unwrap_or_default()
+        })
+        .join("\n")
+}
+
+// size: number of screen commands
+fn year2016_day8(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| match rng.below(3) {
+            0 => format!("rect {}x{}", rng.range(1..51), rng.range(1..7)),
+            1 => format!("rotate row y={} by {}", rng.range(0..6), rng.range(1..50)),
+            _ => format!(
+                "rotate column x={} by {}",
+                rng.range(0..50),
+                rng.range(1..6)
+            ),
+        })
+        .join("\n")
+}
+
+// size: number of top level sections, each nested up to four markers deep
+fn year2016_day9(rng: &mut Rng, size: usize) -> String {
+    fn section(rng: &mut Rng, depth: u32) -> String {
+        if depth == 0 || rng.chance(1, 3) {
+            random_letters(rng, 1..10, UPPERCASE)
+        } else {
+            let inner = (0..rng.range(1..4))
+                .map(|_| section(rng, depth - 1))
+                .collect::<String>();
+            format!("({}x{}){inner}", inner.len(), rng.range(1..10))
+        }
+    }
+
+    (0..size.max(1))
+        .map(|_| {
+            let depth = rng.below(5) as u32;
+            section(rng, depth)
+        })
+        .collect()
+}
+
+// size: number of bots, at most 499 so the chip values stay below 1000. Bots only hand
+// chips to bots with a higher id, so every bot ends up holding exactly two chips and
+// outputs 0, 1 and 2 always get one.
+fn year2016_day10(rng: &mut Rng, size: usize) -> String {
+    let bot_count = size.clamp(3, 499);
+    let mut free_slots = vec![2; bot_count];
+    let mut next_output = 0;
+    let mut lines = Vec::new();
+
+    for bot in 0..bot_count {
+        let mut send = |rng: &mut Rng| {
+            let later_bots = (bot + 1..bot_count)
+                .filter(|&other| free_slots[other] > 0)
+                .collect_vec();
+            if bot + 2 >= bot_count || later_bots.is_empty() || rng.chance(1, 4) {
+                next_output += 1;
+                format!("output {}", next_output - 1)
+            } else {
+                let other = *rng.pick(&later_bots);
+                free_slots[other] -= 1;
+                format!("bot {other}")
+            }
+        };
+        let (low, high) = (send(rng), send(rng));
+        lines.push(format!("bot {bot} gives low to {low} and high to {high}"));
+    }
+
+    let mut values = (1..1000).collect_vec();
+    rng.shuffle(&mut values);
+    let mut values = values.into_iter();
+    for (bot, &free) in free_slots.iter().enumerate() {
+        for _ in 0..free {
+            let value = values.next().expect("bots need fewer than 1000 values");
+            lines.push(format!("value {value} goes to bot {bot}"));
+        }
+    }
+
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+// size: number of lines
+fn year2023_day1(rng: &mut Rng, size: usize) -> String {
+    let words = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    (0..size.max(1))
+        .map(|_| {
+            let mut parts = (0..rng.range(1..8))
+                .map(|_| match rng.below(3) {
+                    0 => rng.pick(&words).to_string(),
+                    1 => rng.range(1..10).to_string(),
+                    _ => random_letters(rng, 1..4, LOWERCASE),
+                })
+                .collect_vec();
+            // Part one needs at least one real digit on every line
+            let idx = rng.index(parts.len() + 1);
+            parts.insert(idx, rng.range(1..10).to_string());
+            parts.concat()
+        })
+        .join("\n")
+}
+
+// size: number of games
+fn year2023_day2(rng: &mut Rng, size: usize) -> String {
+    (1..=size.max(1))
+        .map(|id| {
+            let draws = (0..rng.range(1..7))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    colors[..rng.range(1..4) as usize]
+                        .iter()
+                        .map(|color| format!("{} {color}", rng.range(1..20)))
+                        .join(", ")
+                })
+                .join("; ");
+            format!("Game {id}: {draws}")
+        })
+        .join("\n")
+}
+
+// size: width and height of the schematic
+fn year2023_day3(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| {
+            let mut row = String::new();
+            while row.len() < size {
+                let room = size - row.len();
+                match rng.below(10) {
+                    0..=1 if room >= 2 => {
+                        let digits = (rng.range(1..4) as usize).min(room - 1);
+                        row += &rng.range(1..10_i64.pow(digits as u32)).to_string();
+                        row.push('.');
+                    }
+                    2 => row.push(*rng.pick(b"*#+$/@=%-&") as char),
+                    _ => row.push('.'),
+                }
+            }
+            row
+        })
+        .join("\n")
+}
+
+// size: number of cards
+fn year2023_day4(rng: &mut Rng, size: usize) -> String {
+    let numbers = |rng: &mut Rng, count: usize| {
+        (0..count)
+            .map(|_| format!("{:>2}", rng.range(1..100)))
+            .join(" ")
+    };
+    (1..=size.max(1))
+        .map(|id| format!("Card {id:>3}: {} | {}", numbers(rng, 10), numbers(rng, 25)))
+        .join("\n")
+}
+
+// size: number of mappings in each of the seven layers
+fn year2023_day5(rng: &mut Rng, size: usize) -> String {
+    let seeds = (0..5)
+        .map(|_| {
+            format!(
+                "{} {}",
+                rng.range(0..4_000_000_000),
+                rng.range(1..500_000_000)
+            )
+        })
+        .join(" ");
+    let names = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
+    let layers = names
+        .iter()
+        .tuple_windows()
+        .map(|(from, to)| {
+            let mut source = 0;
+            let mut mappings = (0..size.max(1))
+                .map(|_| {
+                    source += rng.range(0..10_000_000);
+                    let length = rng.range(1..100_000_000);
+                    let mapping = format!("{} {source} {length}", rng.range(0..4_000_000_000));
+                    source += length;
+                    mapping
+                })
+                .collect_vec();
+            rng.shuffle(&mut mappings);
+            format!("{from}-to-{to} map:\n{}", mappings.join("\n"))
+        })
+        .join("\n\n");
+
+    format!("seeds: {seeds}\n\n{layers}")
+}
+
+// size: number of races, at most four since part two glues all times together
+fn year2023_day6(rng: &mut Rng, size: usize) -> String {
+    let races = (0..size.clamp(1, 4))
+        .map(|_| {
+            let time = rng.range(7..100);
+            let best = (time / 2) * (time - time / 2);
+            (time, rng.range(0..best))
+        })
+        .collect_vec();
+
+    format!(
+        "Time:     {}\nDistance: {}",
+        races.iter().map(|(time, _)| format!("{time:>5}")).join(""),
+        races
+            .iter()
+            .map(|(_, record)| format!("{record:>5}"))
+            .join("")
+    )
+}
+
+// size: number of hands
+fn year2023_day7(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}",
+                letters(rng, 5, b"23456789TJQKA"),
+                rng.range(1..1000)
+            )
+        })
+        .join("\n")
+}
+
+// size: length of the instructions and of the longest ghost loop. Each ghost walks a
+// loop xxA -> ... -> xxZ -> (back to the first node after xxA), and AAA ends in ZZZ.
+fn year2023_day8(rng: &mut Rng, size: usize) -> String {
+    fn middle_name(n: usize) -> String {
+        let third = b'B' + (n % 24) as u8;
+        let second = b'A' + (n / 24 % 26) as u8;
+        let first = b'A' + (n / (24 * 26) % 26) as u8;
+        String::from_utf8(vec![first, second, third]).unwrap()
+    }
+
+    let size = size.clamp(2, 2000);
+    let instructions = letters(rng, size, b"LR");
+    let mut middle_count = 0;
+    let mut lines = Vec::new();
+
+    for ghost in 0..(1 + size / 100).min(6) {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_string(), "ZZZ".to_string())
+        } else {
+            let prefix = String::from_utf8(vec![b'A' + ghost as u8, b'A']).unwrap();
+            (format!("{prefix}A"), format!("{prefix}Z"))
+        };
+
+        let middle = (0..rng.range(1..size as i64) as usize)
+            .map(|_| {
+                middle_count += 1;
+                middle_name(middle_count)
+            })
+            .collect_vec();
+
+        let chain = [vec![start], middle.clone(), vec![end]].concat();
+        for (from, to) in chain.iter().tuple_windows() {
+            lines.push(format!("{from} = ({to}, {to})"));
+        }
+        let first = &middle[0];
+        lines.push(format!("{} = ({first}, {first})", chain.last().unwrap()));
+    }
+
+    rng.shuffle(&mut lines);
+    format!("{instructions}\n\n{}", lines.join("\n"))
+}
+
+// size: number of sequences, each from a random polynomial of degree five or less
+fn year2023_day9(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let coefficients = (0..=rng.below(6)).map(|_| rng.range(-5..6)).collect_vec();
+            let start = rng.range(-10..10);
+            (start..start + 21)
+                .map(|x| {
+                    coefficients
+                        .iter()
+                        .rev()
+                        .fold(0, |value, coefficient| value * x + coefficient)
+                })
+                .join(" ")
+        })
+        .join("\n")
+}
+
+// The loop for a maze of the given size, starting in the bottom left corner. It goes up
+// the left side, along a random staircase to the right side and back along the bottom.
+fn maze_loop(rng: &mut Rng, size: usize) -> Vec<(usize, usize)> {
+    fn step_to(path: &mut Vec<(usize, usize)>, target: (usize, usize)) {
+        while *path.last().unwrap() != target {
+            let (x, y) = *path.last().unwrap();
+            let next = match (target.0.cmp(&x), target.1.cmp(&y)) {
+                (Ordering::Greater, _) => (x + 1, y),
+                (_, Ordering::Less) => (x, y - 1),
+                (_, Ordering::Greater) => (x, y + 1),
+                _ => (x - 1, y),
+            };
+            path.push(next);
+        }
+    }
+
+    let bottom = size - 2;
+    let right = size - 2;
+    let mut path = vec![(1, bottom)];
+
+    let mut top = rng.range(1..bottom as i64) as usize;
+    step_to(&mut path, (1, top));
+    for x in 2..right {
+        step_to(&mut path, (x, top));
+        top = rng.range(1..bottom as i64) as usize;
+        step_to(&mut path, (x, top));
+    }
+    step_to(&mut path, (right, top));
+    step_to(&mut path, (right, bottom));
+    step_to(&mut path, (2, bottom));
+    path
+}
+
+// size: width and height of the maze, with junk pipes around the loop
+fn year2023_day10(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(5);
+    let bottom = size - 2;
+    let path = maze_loop(rng, size);
+
+    let mut grid = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *rng.pick(b"....|-LJ7F") as char)
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let len = path.len();
+    for (i, &(x, y)) in path.iter().enumerate() {
+        let neighbours = [path[(i + len - 1) % len], path[(i + 1) % len]];
+        let has = |dx: i64, dy: i64| {
+            neighbours
+                .iter()
+                .any(|&(nx, ny)| nx as i64 - x as i64 == dx && ny as i64 - y as i64 == dy)
+        };
+        grid[y][x] = match (has(0, -1), has(1, 0), has(0, 1), has(-1, 0)) {
+            (true, _, true, _) => '|',
+            (_, true, _, true) => '-',
+            (true, true, _, _) => 'L',
+            (true, _, _, true) => 'J',
+            (_, _, true, true) => '7',
+            _ => 'F',
+        };
+    }
+
+    // The start is the bottom left corner, junk next to it must not connect to it
+    grid[bottom][1] = 'S';
+    grid[bottom][0] = '.';
+    grid[bottom + 1][1] = '.';
+
+    grid.into_iter().map(String::from_iter).join("\n")
+}
+
+// size: width and height of the sky, about a quarter of the rows and columns are empty
+fn year2023_day11(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    let empty_rows = (0..size).map(|_| rng.chance(1, 4)).collect_vec();
+    let empty_cols = (0..size).map(|_| rng.chance(1, 4)).collect_vec();
+
+    let mut sky = (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    let star = !empty_rows[y] && !empty_cols[x] && rng.chance(1, 20);
+                    if star {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+    // At least a pair of stars to measure between
+    sky[0][0] = '#';
+    sky[size - 1][size - 1] = '#';
+
+    sky.into_iter().map(String::from_iter).join("\n")
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let usage = "Usage: generate <year> <day> [--size <n>] [--seed <s>]";
+    let mut positional = Vec::new();
+    let mut size = 100;
+    let mut seed = 0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "--size" => args.next().and_then(|v| v.parse().ok()).map(|v| size = v),
+            "--seed" => args.next().and_then(|v| v.parse().ok()).map(|v| seed = v),
+            _ => arg.parse::<u32>().ok().map(|v| positional.push(v)),
+        };
+        if parsed.is_none() {
+            eprintln!("{usage}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let [year, day] = positional[..] else {
+        eprintln!("{usage}");
+        return ExitCode::FAILURE;
+    };
+    let Some(generate) = generator(year, day) else {
+        eprintln!("No generator for {year} day {day}");
+        return ExitCode::FAILURE;
+    };
+
+    println!("{}", generate(&mut Rng::new(seed), size));
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test_generate {
+    use super::*;
+    use crate::solutions;
+
+    #[test]
+    fn test_every_day_has_a_generator() {
+        for &(year, day) in solutions::DAYS {
+            assert!(generator(year, day).is_some(), "{year} day {day}");
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_input() {
+        for &(year, day) in solutions::DAYS {
+            let generate = generator(year, day).unwrap();
+            assert_eq!(
+                generate(&mut Rng::new(3), 20),
+                generate(&mut Rng::new(3), 20)
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for &(year, day) in solutions::DAYS {
+            // Mining md5 hashes takes far too long for a unit test
+            if (year, day) == (2016, 5) {
+                continue;
+            }
+            let generate = generator(year, day).unwrap();
+            let (part1, part2) = solutions::solvers(year, day).unwrap();
+            for seed in 0..3 {
+                let input = generate(&mut Rng::new(seed), 12);
+                part1(&input);
+                part2(&input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_maze_loop() {
+        for seed in 0..10 {
+            let path = maze_loop(&mut Rng::new(seed), 8);
+
+            assert_eq!(
+                path.len(),
+                path.iter().unique().count(),
+                "loop crosses itself"
+            );
+            let (first, last) = (path[0], path[path.len() - 1]);
+            assert!(path
+                .iter()
+                .chain([&first])
+                .tuple_windows()
+                .all(|(a, b)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1));
+
+            let input = year2023_day10(&mut Rng::new(seed), 8);
+            let farthest = solutions::year2023::day10::solve_part1(&input);
+            assert_eq!((path.len() / 2).to_string(), farthest);
+        }
+    }
+
+    #[test]
+    fn test_ghost_starts_and_ends() {
+        let input = year2023_day8(&mut Rng::new(5), 300);
+        let nodes = |suffix: char| {
+            input
+                .lines()
+                .filter(|line| line.chars().nth(2) == Some(suffix) && line.contains(" = "))
+                .count()
+        };
+        assert_eq!(4, nodes('A'));
+        assert_eq!(4, nodes('Z'));
+        assert!(input.contains("AAA = (") && input.contains("ZZZ = ("));
+    }
+}
@@ -1,21 +1,77 @@
-
+mod alloc_stats;
+mod bench;
+mod fuzz;
+mod generate;
 mod solutions;
+mod utils;
+
+use std::{env, fs, io, process::ExitCode};
+
+use solutions::Solver;
 
-use std::fs;
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: alloc_stats::CountingAlloc = alloc_stats::CountingAlloc;
 
 // https://adventofcode.com/2016/
 
-fn main() {
-    let day = "day11";
+// Inputs live in inputs/<year>/day<n>.txt, older ones directly in inputs/day<n>.txt
+fn read_input(year: u32, day: u32) -> io::Result<String> {
+    fs::read_to_string(format!("inputs/{year}/day{day}.txt"))
+        .or_else(|_| fs::read_to_string(format!("inputs/day{day}.txt")))
+}
+
+fn parse_year_day(args: &[String]) -> Option<(u32, u32)> {
+    match args {
+        [] => Some((2023, 11)),
+        [year, day, ..] => Some((year.parse().ok()?, day.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn solve_part(day: u32, part: u32, solver: Solver, input: &str) {
+    let (answer, stats) = alloc_stats::measure(|| solver(input));
+    println!("Solition to day{day} part{part} is: \n{answer}");
+
+    if alloc_stats::enabled() {
+        println!("Memory for day{day} part{part}: {stats}");
+    }
+}
+
+fn run(args: &[String]) -> ExitCode {
+    // Makes helpers like the memo caches report what they did
+    let verbose = args.iter().any(|arg| arg == "--verbose");
+    utils::memo::set_verbose(verbose);
+    let args = args
+        .iter()
+        .filter(|arg| *arg != "--verbose")
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let Some((year, day)) = parse_year_day(&args) else {
+        eprintln!("Usage: <year> <day> [--verbose] | generate <year> <day> [--size <n>] [--seed <s>] | fuzz [<year> [<day>]] [--iterations <n>] [--seed <s>] | bench [<year> [<day>]] [--compare] [--baseline <commit>] [--threshold <percent>] [--runs <n>]");
+        return ExitCode::FAILURE;
+    };
+    let Some((part1, part2)) = solutions::solvers(year, day) else {
+        eprintln!("No solution for {year} day {day}");
+        return ExitCode::FAILURE;
+    };
+
+    let contents = read_input(year, day).expect("Should have been able to read the file");
 
-    let file_path = format!("inputs/{day}.txt");
+    solve_part(day, 1, part1, &contents);
+    solve_part(day, 2, part2, &contents);
 
-    let contents = fs::read_to_string(file_path)
-        .expect("Should have been able to read the file");
+    ExitCode::SUCCESS
+}
 
-    let answer = solutions::year2023::day11::solve_part1(&contents);
-    println!("Solition to {day} part1 is: \n{answer}");
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<String>>();
 
-    let answer = solutions::year2023::day11::solve_part2(&contents);
-    println!("Solition to {day} part2 is: \n{answer}");
-}
\ No newline at end of file
+    match args.first().map(String::as_str) {
+        Some("bench") => bench::run(&args[1..]),
+        Some("generate") => generate::run(&args[1..]),
+        Some("fuzz") => fuzz::run(&args[1..]),
+        _ => run(&args),
+    }
+}
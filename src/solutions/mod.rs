@@ -1,2 +1,50 @@
 pub mod year2016;
-pub mod year2023;
\ No newline at end of file
+pub mod year2023;
+
+pub type Solver = fn(&str) -> String;
+
+macro_rules! registry {
+    ($($year:literal => $year_mod:ident { $($day:literal => $day_mod:ident),* $(,)? }),* $(,)?) => {
+        // Every (year, day) pair that has a solution, in order
+        pub const DAYS: &[(u32, u32)] = &[$($(($year, $day)),*),*];
+
+        // Looks up both parts of a day, with the answers turned into strings
+        pub fn solvers(year: u32, day: u32) -> Option<(Solver, Solver)> {
+            match (year, day) {
+                $($(($year, $day) => Some((
+                    (|input: &str| $year_mod::$day_mod::solve_part1(input).to_string()) as Solver,
+                    (|input: &str| $year_mod::$day_mod::solve_part2(input).to_string()) as Solver,
+                )),)*)*
+                _ => None,
+            }
+        }
+    };
+}
+
+registry! {
+    2016 => year2016 {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+    },
+    2023 => year2023 {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+    },
+}
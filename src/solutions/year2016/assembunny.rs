@@ -0,0 +1,542 @@
+#![allow(unused)]
+
+// The assembunny machine shared by days 12, 23 and 25: four registers and the cpy, inc,
+// dec, jnz, tgl and out instructions. The programs spend nearly all their time in loops
+// that add or multiply one register into another, so those loops are spotted when the
+// machine reaches them and done in one go. They still count every step they stand for,
+// which keeps step limits and step counts the same as running them one by one.
+
+use std::fmt;
+
+use nom::{
+    branch,
+    bytes::complete::tag,
+    character::complete::{self, one_of},
+    combinator,
+    sequence::{preceded, separated_pair},
+    IResult,
+};
+
+use crate::utils::parse::{finish, lines, ParseError};
+
+pub const REGISTERS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(usize),
+    Value(i64),
+}
+
+// A tgl can turn an instruction into one that makes no sense, like `inc 3`. Those stay
+// in the program and are skipped when run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Cpy(Operand, Operand),
+    Inc(Operand),
+    Dec(Operand),
+    Jnz(Operand, Operand),
+    Tgl(Operand),
+    Out(Operand),
+}
+
+impl Instruction {
+    fn toggled(self) -> Instruction {
+        match self {
+            Instruction::Inc(x) => Instruction::Dec(x),
+            Instruction::Dec(x) | Instruction::Tgl(x) | Instruction::Out(x) => Instruction::Inc(x),
+            Instruction::Jnz(x, y) => Instruction::Cpy(x, y),
+            Instruction::Cpy(x, y) => Instruction::Jnz(x, y),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operand::Register(r) => write!(f, "{}", (b'a' + r as u8) as char),
+            Operand::Value(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Cpy(x, y) => write!(f, "cpy {x} {y}"),
+            Instruction::Inc(x) => write!(f, "inc {x}"),
+            Instruction::Dec(x) => write!(f, "dec {x}"),
+            Instruction::Jnz(x, y) => write!(f, "jnz {x} {y}"),
+            Instruction::Tgl(x) => write!(f, "tgl {x}"),
+            Instruction::Out(x) => write!(f, "out {x}"),
+        }
+    }
+}
+
+pub fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
+    fn operand(input: &str) -> IResult<&str, Operand> {
+        branch::alt((
+            combinator::map(one_of("abcd"), |r| {
+                Operand::Register((r as u8 - b'a') as usize)
+            }),
+            combinator::map(complete::i64, Operand::Value),
+        ))(input)
+    }
+
+    fn two_operands(input: &str) -> IResult<&str, (Operand, Operand)> {
+        separated_pair(operand, tag(" "), operand)(input)
+    }
+
+    branch::alt((
+        combinator::map(preceded(tag("cpy "), two_operands), |(x, y)| {
+            Instruction::Cpy(x, y)
+        }),
+        combinator::map(preceded(tag("inc "), operand), Instruction::Inc),
+        combinator::map(preceded(tag("dec "), operand), Instruction::Dec),
+        combinator::map(preceded(tag("jnz "), two_operands), |(x, y)| {
+            Instruction::Jnz(x, y)
+        }),
+        combinator::map(preceded(tag("tgl "), operand), Instruction::Tgl),
+        combinator::map(preceded(tag("out "), operand), Instruction::Out),
+    ))(input)
+}
+
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    finish(input, lines(parse_instruction))
+}
+
+// What a single step of the machine did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Instruction(Instruction),
+    // `inc to; dec from; jnz from -2`, in either order of the first two
+    Add {
+        to: usize,
+        from: usize,
+    },
+    // An add loop from `counter`, which is copied from `from` before every one of `times`
+    // rounds
+    Multiply {
+        to: usize,
+        from: Operand,
+        counter: usize,
+        times: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trace {
+    // Where the step started
+    pub pc: usize,
+    pub op: Op,
+    // The instructions it stands for
+    pub steps: u64,
+    // After the step
+    pub registers: [i64; REGISTERS],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    // The program counter left the program
+    Halted,
+    // An out instruction ran, running again carries on after it
+    Output(i64),
+    StepLimit,
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine {
+    pub registers: [i64; REGISTERS],
+    program: Vec<Instruction>,
+    pc: i64,
+    steps: u64,
+    peephole: bool,
+}
+
+impl Machine {
+    pub fn new(program: Vec<Instruction>) -> Machine {
+        Machine {
+            registers: [0; REGISTERS],
+            program,
+            pc: 0,
+            steps: 0,
+            peephole: true,
+        }
+    }
+
+    // Runs every loop an instruction at a time, mostly to check the fused loops against
+    pub fn without_peephole(mut self) -> Machine {
+        self.peephole = false;
+        self
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    // Instructions run so far, counting the ones fused loops stood for
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn run(&mut self, limit: u64) -> Stop {
+        self.run_traced(limit, |_| {})
+    }
+
+    // Runs until the program halts, outputs a value or `limit` more instructions have run
+    pub fn run_traced(&mut self, limit: u64, mut trace: impl FnMut(&Trace)) -> Stop {
+        let end = self.steps.saturating_add(limit);
+        loop {
+            let Some(pc) = usize::try_from(self.pc)
+                .ok()
+                .filter(|&pc| pc < self.program.len())
+            else {
+                return Stop::Halted;
+            };
+            let budget = end - self.steps;
+            if budget == 0 {
+                return Stop::StepLimit;
+            }
+
+            let fused = if self.peephole {
+                self.fused_loop(pc).filter(|&(_, steps, _)| steps <= budget)
+            } else {
+                None
+            };
+            let (op, steps, output) = match fused {
+                Some((op, steps, length)) => {
+                    self.apply(op);
+                    self.pc += length;
+                    (op, steps, None)
+                }
+                None => {
+                    let instruction = self.program[pc];
+                    (
+                        Op::Instruction(instruction),
+                        1,
+                        self.execute(pc, instruction),
+                    )
+                }
+            };
+            self.steps += steps;
+            trace(&Trace {
+                pc,
+                op,
+                steps,
+                registers: self.registers,
+            });
+            if let Some(value) = output {
+                return Stop::Output(value);
+            }
+        }
+    }
+
+    fn value(&self, operand: Operand) -> i64 {
+        match operand {
+            Operand::Register(r) => self.registers[r],
+            Operand::Value(v) => v,
+        }
+    }
+
+    // Returns what an out instruction sent
+    fn execute(&mut self, pc: usize, instruction: Instruction) -> Option<i64> {
+        let mut jump = 1;
+        let mut output = None;
+        match instruction {
+            Instruction::Cpy(x, Operand::Register(r)) => self.registers[r] = self.value(x),
+            Instruction::Inc(Operand::Register(r)) => self.registers[r] += 1,
+            Instruction::Dec(Operand::Register(r)) => self.registers[r] -= 1,
+            Instruction::Jnz(x, y) => {
+                if self.value(x) != 0 {
+                    jump = self.value(y);
+                }
+            }
+            Instruction::Tgl(x) => {
+                let target = (pc as i64).checked_add(self.value(x));
+                if let Some(target) = target.and_then(|t| usize::try_from(t).ok()) {
+                    if let Some(instruction) = self.program.get_mut(target) {
+                        *instruction = instruction.toggled();
+                    }
+                }
+            }
+            Instruction::Out(x) => output = Some(self.value(x)),
+            // Made invalid by a tgl
+            Instruction::Cpy(_, Operand::Value(_))
+            | Instruction::Inc(Operand::Value(_))
+            | Instruction::Dec(Operand::Value(_)) => {}
+        }
+        self.pc = self.pc.saturating_add(jump);
+        output
+    }
+
+    // The loop starting at pc, if there is one that can be done in one go, with the steps
+    // it stands for and how many instructions it covers
+    fn fused_loop(&self, pc: usize) -> Option<(Op, u64, i64)> {
+        use Instruction::*;
+        use Operand::*;
+
+        let add = |at: usize| -> Option<(usize, usize)> {
+            let (to, from) = match self.program.get(at..at + 3)? {
+                [Inc(Register(to)), Dec(Register(from)), Jnz(Register(test), Value(-2))]
+                | [Dec(Register(from)), Inc(Register(to)), Jnz(Register(test), Value(-2))]
+                    if test == from && to != from =>
+                {
+                    (*to, *from)
+                }
+                _ => return None,
+            };
+            Some((to, from))
+        };
+
+        if let [Cpy(from, Register(counter)), ..] = self.program[pc..] {
+            let multiply = match (add(pc + 1), self.program.get(pc + 4..pc + 6)) {
+                (
+                    Some((to, inner)),
+                    Some(&[Dec(Register(times)), Jnz(Register(test), Value(-5))]),
+                ) if inner == counter
+                    && test == times
+                    && times != to
+                    && times != counter
+                    && from != Register(to)
+                    && from != Register(counter)
+                    && from != Register(times) =>
+                {
+                    Some((to, times))
+                }
+                _ => None,
+            };
+            if let Some((to, times)) = multiply {
+                let (rounds, count) = (self.registers[times], self.value(from));
+                if rounds > 0 && count > 0 {
+                    // Each round is the cpy, the add loop, the dec and the jnz
+                    let steps = (rounds as u64)
+                        .checked_mul((count as u64).checked_mul(3)?.checked_add(3)?)?;
+                    let op = Op::Multiply {
+                        to,
+                        from,
+                        counter,
+                        times,
+                    };
+                    return Some((op, steps, 6));
+                }
+            }
+        }
+
+        let (to, from) = add(pc)?;
+        let count = self.registers[from];
+        if count <= 0 {
+            return None;
+        }
+        // The inc, the dec and the jnz for every count
+        let steps = (count as u64).checked_mul(3)?;
+        Some((Op::Add { to, from }, steps, 3))
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Instruction(_) => unreachable!("Only fused loops are applied"),
+            Op::Add { to, from } => {
+                self.registers[to] += self.registers[from];
+                self.registers[from] = 0;
+            }
+            Op::Multiply {
+                to,
+                from,
+                counter,
+                times,
+            } => {
+                self.registers[to] += self.value(from) * self.registers[times];
+                self.registers[counter] = 0;
+                self.registers[times] = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_assembunny {
+    use super::*;
+    use crate::utils::prop::check;
+
+    fn run(source: &str) -> Machine {
+        let mut machine = Machine::new(parse_program(source).unwrap());
+        assert_eq!(Stop::Halted, machine.run(u64::MAX));
+        machine
+    }
+
+    #[test]
+    fn test_parse_instruction() {
+        use Operand::*;
+        assert_eq!(
+            Ok(("", Instruction::Cpy(Value(-41), Register(0)))),
+            parse_instruction("cpy -41 a")
+        );
+        assert_eq!(
+            Ok(("", Instruction::Jnz(Register(2), Value(-2)))),
+            parse_instruction("jnz c -2")
+        );
+        assert!(parse_instruction("inc e").is_err());
+
+        let source = "cpy 2 a\ntgl a\nout b\ninc d\ndec c\njnz 1 d";
+        let program = parse_program(source).unwrap();
+        assert_eq!(
+            source,
+            program
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_is_located() {
+        let error = parse_program("inc a\ncpy 1 x").unwrap_err();
+        assert_eq!((2, 1), (error.line, error.column));
+    }
+
+    #[test]
+    fn test_copy_and_jump() {
+        let machine = run("cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a");
+        assert_eq!(42, machine.registers[0]);
+        assert_eq!(5, machine.steps());
+    }
+
+    #[test]
+    fn test_toggle() {
+        // Toggles skip targets outside the program and can make invalid instructions
+        let machine = run("cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a");
+        assert_eq!(3, machine.registers[0]);
+    }
+
+    #[test]
+    fn test_output_resumes() {
+        let mut machine = Machine::new(parse_program("out 1\nout a\ninc a\njnz 1 -3").unwrap());
+        let outputs = (0..5).map(|_| machine.run(100)).collect::<Vec<_>>();
+        assert_eq!(
+            vec![1, 0, 1, 1, 1]
+                .into_iter()
+                .map(Stop::Output)
+                .collect::<Vec<_>>(),
+            outputs
+        );
+        assert_eq!(Stop::Output(2), machine.run(100));
+        assert_eq!(Stop::StepLimit, machine.run(2));
+        assert_eq!(12, machine.steps());
+    }
+
+    const MULTIPLY: &str = "cpy 6 b\ncpy 7 d\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5";
+
+    #[test]
+    fn test_peephole_multiply() {
+        let mut traces = Vec::new();
+        let mut machine = Machine::new(parse_program(MULTIPLY).unwrap());
+        machine.run_traced(u64::MAX, |trace| traces.push(*trace));
+        assert_eq!([42, 6, 0, 0], machine.registers);
+        assert_eq!(
+            Op::Multiply {
+                to: 0,
+                from: Operand::Register(1),
+                counter: 2,
+                times: 3
+            },
+            traces[2].op
+        );
+        assert_eq!(3, traces.len());
+
+        // The same steps without it
+        let mut plain = Machine::new(parse_program(MULTIPLY).unwrap()).without_peephole();
+        plain.run(u64::MAX);
+        assert_eq!(machine.registers, plain.registers);
+        assert_eq!(machine.steps(), plain.steps());
+    }
+
+    #[test]
+    fn test_peephole_is_fast() {
+        // Around 3 * 10^12 steps one at a time
+        let mut machine = Machine::new(
+            parse_program(
+                "cpy 1000000 b\ncpy 1000000 d\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5",
+            )
+            .unwrap(),
+        );
+        assert_eq!(Stop::Halted, machine.run(u64::MAX));
+        assert_eq!(1_000_000_000_000, machine.registers[0]);
+    }
+
+    #[test]
+    fn test_step_limit_inside_loop() {
+        // A loop that doesn't fit in what is left runs one step at a time up to the limit
+        let mut machine = Machine::new(parse_program(MULTIPLY).unwrap());
+        let mut plain = Machine::new(parse_program(MULTIPLY).unwrap()).without_peephole();
+        for limit in [1, 7, 30, 200] {
+            assert_eq!(plain.run(limit), machine.run(limit));
+            assert_eq!(plain.registers, machine.registers);
+            assert_eq!((plain.pc(), plain.steps()), (machine.pc(), machine.steps()));
+        }
+    }
+
+    #[test]
+    fn test_add_loop_too_long_to_count() {
+        // Three steps for each of i64::MAX rounds is more than a u64 holds
+        let source = format!("cpy {} b\ninc a\ndec b\njnz b -2", i64::MAX);
+        let mut machine = Machine::new(parse_program(&source).unwrap());
+        let mut plain = Machine::new(parse_program(&source).unwrap()).without_peephole();
+        assert_eq!(plain.run(100), machine.run(100));
+        assert_eq!(plain.registers, machine.registers);
+    }
+
+    #[test]
+    fn prop_peephole_changes_nothing() {
+        // Random programs over a few registers and small jumps, with add and multiply
+        // loops thrown in
+        let pieces = [
+            "inc a",
+            "dec b",
+            "cpy c b",
+            "jnz b -2",
+            "jnz d 2",
+            "tgl c",
+            "dec d",
+            "inc c",
+            "cpy 3 c",
+            "cpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5",
+            "inc a\ndec b\njnz b -2",
+        ];
+        check(
+            "fused loops give the same machine as single steps",
+            |rng| {
+                let registers = (0..4).map(|_| rng.range(-3..6)).collect::<Vec<_>>();
+                let program = (0..rng.range(1..12))
+                    .map(|_| rng.range(0..pieces.len() as i64) as usize)
+                    .collect::<Vec<_>>();
+                (registers, program)
+            },
+            |(registers, program)| {
+                let source = program
+                    .iter()
+                    .map(|&piece| pieces[piece % pieces.len()])
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let Ok(program) = parse_program(&source) else {
+                    return true;
+                };
+                let mut fused = Machine::new(program.clone());
+                for (register, &value) in fused.registers.iter_mut().zip(registers) {
+                    *register = value;
+                }
+                let mut plain = fused.clone().without_peephole();
+                (0..5).all(|_| {
+                    plain.run(50) == fused.run(50)
+                        && plain.registers == fused.registers
+                        && plain.pc() == fused.pc()
+                        && plain.steps() == fused.steps()
+                        && plain.program() == fused.program()
+                })
+            },
+        )
+    }
+}
@@ -2,16 +2,19 @@
 
 // https://adventofcode.com/2016/day/1
 
-use std::collections::HashSet;
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::digit1,
-    combinator::{map_res, opt},
-    multi::fold_many0,
-    IResult,
+    branch::alt, bytes::complete::tag, character::complete::digit1, combinator::map_res,
+    multi::separated_list1, IResult,
 };
 
+use crate::utils::{
+    direction::Direction,
+    parse::{finish, ParseError},
+    point::Point2,
+};
+
+type Location = Point2<i64>;
+
 #[derive(PartialEq, Debug)]
 struct WalkInstruction {
     turn_direction: TurnDirection,
@@ -25,31 +28,17 @@ enum TurnDirection {
     Forward,
 }
 
-#[derive(Clone, Copy, Debug)]
-enum Direction {
-    NORTH,
-    WEST,
-    SOUTH,
-    EAST,
-}
-
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-struct Location {
-    x: i32,
-    y: i32,
-}
-
 #[derive(Clone, Copy, Debug)]
 struct Walker {
     location: Location,
     dir: Direction,
 }
 
-fn parse_turn_walk(input: &str) -> Vec<WalkInstruction> {
+// An empty input is no turns
+fn parse_turn_walk(input: &str) -> Result<Vec<WalkInstruction>, ParseError> {
     fn parse_turn(input: &str) -> IResult<&str, WalkInstruction> {
         let (input, out) = alt((tag("L"), tag("R")))(input)?;
         let (input, distance) = map_res(digit1, str::parse)(input)?;
-        let (input, _) = opt(tag(", "))(input)?;
 
         let turn_direction = match out {
             "R" => TurnDirection::Right,
@@ -65,70 +54,29 @@ fn parse_turn_walk(input: &str) -> Vec<WalkInstruction> {
         ))
     }
 
-    let (_, turns) = fold_many0(parse_turn, Vec::new, |mut acc: Vec<_>, item| {
-        acc.push(item);
-        acc
-    })(input)
-    .unwrap_or_default();
-
-    turns
-}
-
-impl Direction {
-    fn left(self) -> Direction {
-        match self {
-            Direction::NORTH => Direction::WEST,
-            Direction::WEST => Direction::SOUTH,
-            Direction::SOUTH => Direction::EAST,
-            Direction::EAST => Direction::NORTH,
-        }
-    }
-
-    fn right(self) -> Direction {
-        match self {
-            Direction::NORTH => Direction::EAST,
-            Direction::EAST => Direction::SOUTH,
-            Direction::SOUTH => Direction::WEST,
-            Direction::WEST => Direction::NORTH,
-        }
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
     }
+    finish(input, separated_list1(tag(", "), parse_turn))
 }
 
 impl Walker {
     fn turn_left(self) -> Walker {
         Walker {
-            dir: self.dir.left(),
+            dir: self.dir.turn_left(),
             ..self
         }
     }
 
     fn turn_right(self) -> Walker {
         Walker {
-            dir: self.dir.right(),
+            dir: self.dir.turn_right(),
             ..self
         }
     }
 
     fn walk(self, distance: &u32) -> Walker {
-        let distance = *distance as i32;
-        let new_location = match self.dir {
-            Direction::NORTH => Location {
-                y: self.location.y - distance,
-                ..self.location
-            },
-            Direction::WEST => Location {
-                x: self.location.x - distance,
-                ..self.location
-            },
-            Direction::SOUTH => Location {
-                y: self.location.y + distance,
-                ..self.location
-            },
-            Direction::EAST => Location {
-                x: self.location.x + distance,
-                ..self.location
-            },
-        };
+        let new_location = self.location + Location::from(self.dir) * *distance as i64;
 
         Walker {
             location: new_location,
@@ -147,72 +95,85 @@ impl Walker {
     fn origin() -> Walker {
         Walker {
             location: Location::origin(),
-            dir: Direction::NORTH,
+            dir: Direction::North,
         }
     }
 }
 
-impl Location {
-    fn origin() -> Location {
-        Location { x: 0, y: 0 }
-    }
-
-    fn distance_to(&self, origin: Location) -> u32 {
-        self.x.abs_diff(origin.x) + self.y.abs_diff(origin.x)
-    }
-}
-
-pub fn solve_part1(input: &str) -> u32 {
-    let turns = parse_turn_walk(input);
+pub fn solve_part1(input: &str) -> String {
+    let turns = match parse_turn_walk(input) {
+        Ok(turns) => turns,
+        Err(err) => return format!("{err}"),
+    };
 
     let end_loc = turns
         .iter()
         .fold(Walker::origin(), |acc, elem| acc.turn_walk(elem));
 
-    end_loc.location.distance_to(Location::origin())
+    end_loc.location.manhattan(Location::origin()).to_string()
 }
 
-// Find the first location visited twice
-pub fn solve_part2(input: &str) -> u32 {
-    let turns = parse_turn_walk(input);
+// The fewest blocks walked from `start` in steps of `step` before standing on the
+// straight line between `a` and `b`, not counting `start` itself
+fn blocks_until_crossing(
+    start: Location,
+    step: Location,
+    distance: i64,
+    (a, b): (Location, Location),
+) -> Option<i64> {
+    // The blocks for which one coordinate is between the ends of the line
+    fn blocks_between(start: i64, step: i64, low: i64, high: i64) -> Option<(i64, i64)> {
+        if step == 0 {
+            (low..=high)
+                .contains(&start)
+                .then_some((i64::MIN, i64::MAX))
+        } else {
+            let (first, last) = ((low - start) * step, (high - start) * step);
+            Some((first.min(last), first.max(last)))
+        }
+    }
 
-    let end_loc = turns
-        .iter()
-        .flat_map(|wi| {
-            // Expands each instruction into many so that only one block is walked at a time
-            (0..wi.distance).map(|i| {
-                if i == 0 {
-                    WalkInstruction {
-                        distance: 1,
-                        turn_direction: wi.turn_direction.clone(),
-                    }
-                } else {
-                    WalkInstruction {
-                        distance: 1,
-                        turn_direction: TurnDirection::Forward,
-                    }
-                }
+    let (x_first, x_last) = blocks_between(start.x, step.x, a.x.min(b.x), a.x.max(b.x))?;
+    let (y_first, y_last) = blocks_between(start.y, step.y, a.y.min(b.y), a.y.max(b.y))?;
+    let (first, last) = (
+        x_first.max(y_first).max(1),
+        x_last.min(y_last).min(distance),
+    );
+    (first <= last).then_some(first)
+}
+
+// Find the first location visited twice. The walk is kept as the lines between its
+// corners, so long walks don't have to be taken one block at a time.
+pub fn solve_part2(input: &str) -> String {
+    let turns = match parse_turn_walk(input) {
+        Ok(turns) => turns,
+        Err(err) => return format!("{err}"),
+    };
+
+    // The origin is a line of its own, it has been visited before the first turn
+    let mut corners = vec![Location::origin(), Location::origin()];
+    let mut walker = Walker::origin();
+    for turn in &turns {
+        let start = walker.location;
+        walker = walker.turn_walk(turn);
+        let step = Location::from(walker.dir);
+
+        let crossing = corners
+            .windows(2)
+            .filter_map(|line| {
+                blocks_until_crossing(start, step, turn.distance as i64, (line[0], line[1]))
             })
-        })
-        .scan(
-            (HashSet::<Location>::new(), Walker::origin()),
-            |(visited, walker), instr| {
-                // before we walk
-                let not_duplicate = visited.insert(walker.location);
-
-                *walker = walker.turn_walk(&instr);
-
-                if not_duplicate {
-                    Some(walker.location)
-                } else {
-                    None
-                }
-            },
-        )
-        .last()
-        .unwrap();
+            .min();
+        if let Some(blocks) = crossing {
+            return (start + step * blocks)
+                .manhattan(Location::origin())
+                .to_string();
+        }
+        corners.push(walker.location);
+    }
 
-    end_loc.distance_to(Location::origin())
+    // Nowhere was visited twice
+    walker.location.manhattan(Location::origin()).to_string()
 }
 
 #[cfg(test)]
@@ -222,21 +183,21 @@ mod test_day1 {
     #[test]
     fn test_parse_turn_walk() {
         assert_eq!(
-            vec![WalkInstruction {
+            Ok(vec![WalkInstruction {
                 turn_direction: TurnDirection::Left,
                 distance: 5
-            }],
+            }]),
             parse_turn_walk("L5")
         );
         assert_eq!(
-            vec![WalkInstruction {
+            Ok(vec![WalkInstruction {
                 turn_direction: TurnDirection::Right,
                 distance: 52
-            }],
+            }]),
             parse_turn_walk("R52")
         );
         assert_eq!(
-            vec![
+            Ok(vec![
                 WalkInstruction {
                     turn_direction: TurnDirection::Left,
                     distance: 1
@@ -249,15 +210,25 @@ mod test_day1 {
                     turn_direction: TurnDirection::Left,
                     distance: 23
                 }
-            ],
-            parse_turn_walk("L1, R3, L23")
+            ]),
+            parse_turn_walk("L1, R3, L23\n")
         );
     }
 
+    #[test]
+    fn test_bad_turns() {
+        let error = parse_turn_walk("L1, X3").unwrap_err();
+        assert_eq!((1, 3), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2("L1, X3"));
+        assert!(parse_turn_walk("L99999999999").is_err());
+        assert_eq!("0", solve_part1(""));
+        assert_eq!("0", solve_part2(""));
+    }
+
     #[test]
     fn test_simple() {
         let input = "R2, L3";
-        let expected = 5;
+        let expected = "5";
         let result = solve_part1(input);
 
         assert_eq!(result, expected);
@@ -266,7 +237,7 @@ mod test_day1 {
     #[test]
     fn test_half_circle() {
         let input = "R2, R2, R2";
-        let expected = 2;
+        let expected = "2";
         let result = solve_part1(input);
 
         assert_eq!(result, expected);
@@ -275,7 +246,7 @@ mod test_day1 {
     #[test]
     fn test_long_example() {
         let input = "R5, L5, R5, R3";
-        let expected = 12;
+        let expected = "12";
         let result = solve_part1(input);
 
         assert_eq!(result, expected);
@@ -284,9 +255,19 @@ mod test_day1 {
     #[test]
     fn test_visited_twice() {
         let input = "R8, R4, R4, R8";
-        let expected = 4;
+        let expected = "4";
         let result = solve_part2(input);
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_visited_twice_far_away() {
+        // Turning round walks back over the last line
+        assert_eq!("2", solve_part2("R3, R0, R1"));
+        assert_eq!("0", solve_part2("R2, L2, L2, L2"));
+        // Too far to walk one block at a time
+        assert_eq!("524967357", solve_part2("R61, R524967296"));
+        assert_eq!("3999999999", solve_part2("L4000000000, L1, L1, L1"));
+    }
 }
@@ -1,15 +1,8 @@
 #![allow(unused)]
 
 use itertools::Itertools;
-use nom::{
-    branch,
-    bytes::complete::tag,
-    character::{self, complete},
-    combinator, multi,
-    sequence::{self, preceded, tuple},
-    IResult,
-};
-use std::collections::{HashMap, VecDeque};
+use pattern_derive::FromPattern;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug, Default)]
 struct Bot {
@@ -22,72 +15,43 @@ struct Output {
     chips: Vec<Chip>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, FromPattern)]
+#[pattern("low to {give_low} and high to {give_high}")]
 struct BotLogic {
     give_low: SendId,
     give_high: SendId,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, FromPattern)]
+#[pattern("{0}")]
 struct Chip(u32);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, FromPattern)]
 enum SendId {
+    #[pattern("bot {0}")]
     Bot(u32),
+    #[pattern("output {0}")]
     Output(u32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, FromPattern)]
 enum Instruction {
+    #[pattern("value {0} goes to bot {1}")]
     ValueTo(Chip, u32),
+    #[pattern("bot {0} gives {1}")]
     BotInstr(u32, BotLogic),
 }
 
-fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
-    fn send_id(input: &str) -> IResult<&str, SendId> {
-        branch::alt((
-            combinator::map(preceded(tag("bot "), complete::u32), SendId::Bot),
-            combinator::map(preceded(tag("output "), complete::u32), SendId::Output),
-        ))(input)
-    }
-
-    fn bot_logic(input: &str) -> IResult<&str, BotLogic> {
-        let (input, (give_low, give_high)) = tuple((
-            sequence::preceded(tag("low to "), send_id),
-            sequence::preceded(tag(" and high to "), send_id),
-        ))(input)?;
-
-        Ok((
-            input,
-            BotLogic {
-                give_low,
-                give_high,
-            },
-        ))
-    }
-
-    branch::alt((
-        combinator::map(
-            tuple((
-                sequence::preceded(tag("value "), complete::u32),
-                sequence::preceded(tag(" goes to bot "), complete::u32),
-            )),
-            |(value, send_id)| Instruction::ValueTo(Chip(value), send_id),
-        ),
-        combinator::map(
-            tuple((
-                sequence::preceded(tag("bot "), complete::u32),
-                sequence::preceded(tag(" gives "), bot_logic),
-            )),
-            |(bot_id, logic)| Instruction::BotInstr(bot_id, logic),
-        ),
-    ))(input)
-}
-
-fn parse_instructions(input: &str) -> Vec<Instruction> {
-    multi::separated_list0(character::complete::newline, parse_instruction)(input)
-        .unwrap()
-        .1
+// The first line that isn't an instruction fails the whole list
+fn parse_instructions(input: &str) -> Result<Vec<Instruction>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|error| format!("line {}: {error}", i + 1))
+        })
+        .collect()
 }
 
 pub fn solve_part1(input: &str) -> String {
@@ -95,7 +59,12 @@ pub fn solve_part1(input: &str) -> String {
     let mut bot_queue = VecDeque::<u32>::new();
     let mut outputs = HashMap::<u32, Output>::new();
 
-    for instr in parse_instructions(input) {
+    let instructions = match parse_instructions(input) {
+        Ok(instructions) => instructions,
+        Err(error) => return error,
+    };
+
+    for instr in instructions {
         match instr {
             Instruction::ValueTo(chip, id) => {
                 let bot = bots.entry(id).or_default();
@@ -111,8 +80,13 @@ pub fn solve_part1(input: &str) -> String {
         }
     }
 
-    while !bot_queue.is_empty() {
-        let bot_id = bot_queue.pop_front().unwrap();
+    // Each bot hands out chips once, so bots that give to themselves can't loop forever
+    let mut handed_out = HashSet::<u32>::new();
+
+    while let Some(bot_id) = bot_queue.pop_front() {
+        if !handed_out.insert(bot_id) {
+            continue;
+        }
         bots.get_mut(&bot_id)
             .and_then(|bot| {
                 bot.chips.sort();
@@ -127,8 +101,8 @@ pub fn solve_part1(input: &str) -> String {
                     .as_ref()
                     .and_then(|l| Some([(max_chip, l.give_high), (min_chip, l.give_low)]))
             })
-            .expect("The bot must be listed in the bot map.")
             .into_iter()
+            .flatten()
             .for_each(|(chip, send_id)| match send_id {
                 SendId::Bot(id) => {
                     bots.entry(id).and_modify(|bot| {
@@ -145,10 +119,15 @@ pub fn solve_part1(input: &str) -> String {
             });
     }
 
-    let Chip(val0) = outputs.get(&0).and_then(|out| out.chips.first()).unwrap();
-    let Chip(val1) = outputs.get(&1).and_then(|out| out.chips.first()).unwrap();
-    let Chip(val2) = outputs.get(&2).and_then(|out| out.chips.first()).unwrap();
-    format!("Output value product: {}", (val0 * val1 * val2))
+    let product = (0..3).try_fold(1_u64, |product, id| {
+        let Chip(value) = outputs.get(&id).and_then(|out| out.chips.first())?;
+        product.checked_mul(*value as u64)
+    });
+
+    match product {
+        Some(product) => format!("Output value product: {}", product),
+        None => "Outputs 0, 1 and 2 did not all get a chip".to_string(),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
@@ -162,10 +141,27 @@ mod test_day10 {
     #[test]
     fn test_parse_instructions() {
         let input = "value 5 goes to bot 2\nbot 2 gives low to bot 1 and high to bot 0\nvalue 3 goes to bot 1\nbot 1 gives low to output 1 and high to bot 0\nbot 0 gives low to output 2 and high to output 0\nvalue 2 goes to bot 2";
-        let output = parse_instructions(input);
+        let output = parse_instructions(input).unwrap();
+        assert_eq!(6, output.len());
         println!("{:?}", output);
     }
 
+    #[test]
+    fn test_part1_bad_input() {
+        // Lines after a bad one aren't dropped, the bad line is the answer
+        let input = "value 5 goes to bot 2\nnot an instruction\nvalue 3 goes to bot 1";
+        let error = parse_instructions(input).unwrap_err();
+        assert!(error.starts_with("line 2: "), "{error}");
+        assert_eq!(error, solve_part1(input));
+
+        // A bot that gives its chips to itself
+        let input = "value 1 goes to bot 0\nvalue 2 goes to bot 0\nbot 0 gives low to bot 0 and high to bot 0";
+        assert_eq!(
+            "Outputs 0, 1 and 2 did not all get a chip",
+            solve_part1(input)
+        );
+    }
+
     #[test]
     fn test_part1() {
         let input = "value 5 goes to bot 2\nbot 2 gives low to bot 1 and high to bot 0\nvalue 3 goes to bot 1\nbot 1 gives low to output 1 and high to bot 0\nbot 0 gives low to output 2 and high to output 0\nvalue 2 goes to bot 2";
@@ -2,102 +2,94 @@
 
 // https://adventofcode.com/2016/day/2
 
+use nom::{character::complete::anychar, combinator::map_opt, multi::many1, IResult};
+
+use crate::utils::{
+    direction::Direction,
+    grid::Grid,
+    parse::{finish, lines, ParseError},
+    point::Point2,
+};
+
+// The diamond keypad from part two, padded so every key has four neighbours
+const KEY_PAD: &str = "\
+_______
+___1___
+__234__
+_56789_
+__ABC__
+___D___
+_______";
+
+// One line of moves per button, an empty input is no buttons
+fn parse_moves(input: &str) -> Result<Vec<Vec<Direction>>, ParseError> {
+    fn moves(input: &str) -> IResult<&str, Vec<Direction>> {
+        many1(map_opt(anychar, Direction::from_char))(input)
+    }
+
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(moves))
+}
+
 pub fn solve_part1(input: &str) -> String {
-    input
-        .lines()
+    let moves = match parse_moves(input) {
+        Ok(moves) => moves,
+        Err(err) => return format!("{err}"),
+    };
+
+    moves
+        .iter()
         .scan(5u32, |last_button_pressed, row| {
-            let new_press =
-                row.chars()
-                    .fold(last_button_pressed.clone(), |number, dir| match dir {
-                        'U' if number > 3 => number - 3,
-                        'D' if number < 7 => number + 3,
-                        'L' if number % 3 != 1 => number - 1,
-                        'R' if number % 3 != 0 => number + 1,
-                        _ => number,
-                    });
+            let new_press = row
+                .iter()
+                .fold(*last_button_pressed, |number, dir| match dir {
+                    Direction::North if number > 3 => number - 3,
+                    Direction::South if number < 7 => number + 3,
+                    Direction::West if number % 3 != 1 => number - 1,
+                    Direction::East if number % 3 != 0 => number + 1,
+                    _ => number,
+                });
             *last_button_pressed = new_press;
             Some(new_press.to_string())
         })
         .collect()
 }
 
-#[derive(Clone, Copy, Debug)]
-struct KeyPos {
-    x: u32,
-    y: u32,
-}
+type KeyPos = Point2<usize>;
 
-impl KeyPos {
-    fn up(self) -> KeyPos {
-        KeyPos {
-            y: self.y - 1,
-            ..self
-        }
-    }
-    fn down(self) -> KeyPos {
-        KeyPos {
-            y: self.y + 1,
-            ..self
-        }
-    }
-    fn left(self) -> KeyPos {
-        KeyPos {
-            x: self.x - 1,
-            ..self
-        }
-    }
-    fn right(self) -> KeyPos {
-        KeyPos {
-            x: self.x + 1,
-            ..self
-        }
-    }
-
-    fn get_key(&self) -> char {
-        let key_pad = vec![
-            vec!['_', '_', '_', '_', '_', '_', '_'],
-            vec!['_', '_', '_', '1', '_', '_', '_'],
-            vec!['_', '_', '2', '3', '4', '_', '_'],
-            vec!['_', '5', '6', '7', '8', '9', '_'],
-            vec!['_', '_', 'A', 'B', 'C', '_', '_'],
-            vec!['_', '_', '_', 'D', '_', '_', '_'],
-            vec!['_', '_', '_', '_', '_', '_', '_'],
-        ];
-        key_pad[self.y as usize][self.x as usize]
-    }
-
-    fn valid_or(self, alternative: KeyPos) -> KeyPos {
-        let key = self.get_key();
+fn get_key(pos: KeyPos, key_pad: &Grid<char>) -> char {
+    key_pad[pos.into()]
+}
 
-        if key == '_' {
-            alternative
-        } else {
-            self
-        }
-    }
+// The key the finger ends up on, None when there is no key in that direction
+fn step(pos: KeyPos, dir: Direction, key_pad: &Grid<char>) -> Option<KeyPos> {
+    let next = (pos.try_cast::<i64>()? + dir.into()).try_cast::<usize>()?;
+    key_pad
+        .get(next.x, next.y)
+        .is_some_and(|&key| key != '_')
+        .then_some(next)
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let start_pos = KeyPos { x: 1, y: 4 }; // The five
-
-    input
-        .lines()
+    let start_pos = KeyPos::new(1, 4); // The five
+    let key_pad = Grid::parse(KEY_PAD, |c| c).expect("The key pad is rectangular");
+    let moves = match parse_moves(input) {
+        Ok(moves) => moves,
+        Err(err) => return format!("{err}"),
+    };
+
+    moves
+        .iter()
         .scan(start_pos, |previous_click_pos, line| {
             let new_click_pos =
-                line.chars()
-                    .fold(previous_click_pos.clone(), |previous_finder_pos, dir| {
-                        let new_finger_pos = match dir {
-                            'U' => previous_finder_pos.up(),
-                            'D' => previous_finder_pos.down(),
-                            'L' => previous_finder_pos.left(),
-                            'R' => previous_finder_pos.right(),
-                            _ => panic!("Invalid input"),
-                        };
-
-                        new_finger_pos.valid_or(previous_finder_pos)
+                line.iter()
+                    .fold(*previous_click_pos, |previous_finder_pos, &dir| {
+                        step(previous_finder_pos, dir, &key_pad).unwrap_or(previous_finder_pos)
                     });
             *previous_click_pos = new_click_pos;
-            Some(new_click_pos.get_key())
+            Some(get_key(new_click_pos, &key_pad))
         })
         .collect()
 }
@@ -129,4 +121,12 @@ mod test_day2 {
 
         assert_eq!(expected, solve_part2(input));
     }
+
+    #[test]
+    fn test_bad_moves() {
+        let error = parse_moves("ULL\nRX").unwrap_err();
+        assert_eq!((2, 2), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2("ULL\nRX"));
+        assert_eq!("", solve_part1("\n"));
+    }
 }
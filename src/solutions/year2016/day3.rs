@@ -1,44 +1,63 @@
 #![allow(unused)]
 
-use itertools::Itertools;
+use nom::{
+    character::complete::u32,
+    error::{Error, ErrorKind},
+    IResult,
+};
 
-fn valid_triangle(input: &str) -> bool {
-    let sides = input
-        .split_ascii_whitespace()
-        .map(|s| s.parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
+use crate::utils::parse::{finish, lines, list, ParseError};
 
-    let valid = sides[0] < sides[1] + sides[2]
-        && sides[1] < sides[0] + sides[2]
-        && sides[2] < sides[0] + sides[1];
+type Triangle = [u32; 3];
 
-    return valid;
-}
+// Three side lengths per line, an empty input is no triangles
+fn parse_triangles(input: &str) -> Result<Vec<Triangle>, ParseError> {
+    fn triangle(input: &str) -> IResult<&str, Triangle> {
+        let (input, sides) = list(u32)(input)?;
+        match <Triangle>::try_from(sides) {
+            Ok(sides) => Ok((input, sides)),
+            Err(_) => Err(nom::Err::Failure(Error::new(input, ErrorKind::Count))),
+        }
+    }
 
-pub fn solve_part1(input: &str) -> u32 {
-    input.lines().filter(|&line| valid_triangle(line)).count() as u32
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(triangle))
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-
-    let triplets = input
-        .lines()
-        .enumerate()
-        .map(|(idx, row)| (idx / 3, row))
-        .into_group_map();
-
-    let valid_triangles = triplets.into_iter().map(|(_, t)| {
-        let t = t.join(" ");
+fn valid_triangle(sides: Triangle) -> bool {
+    // Wide enough that the sums can't overflow
+    let [a, b, c] = sides.map(u64::from);
+    a < b + c && b < a + c && c < a + b
+}
 
-        let triangles = [t.split_ascii_whitespace().step_by(3).join(" "),
-                                      t.split_ascii_whitespace().skip(1).step_by(3).join(" "),
-                                      t.split_ascii_whitespace().skip(2).step_by(3).join(" ")];
+pub fn solve_part1(input: &str) -> String {
+    match parse_triangles(input) {
+        Ok(triangles) => triangles
+            .into_iter()
+            .filter(|&triangle| valid_triangle(triangle))
+            .count()
+            .to_string(),
+        Err(err) => format!("{err}"),
+    }
+}
 
-        triangles.into_iter().filter(|triangle| valid_triangle(triangle)).count() as u32
+// The triangles go down the columns, three rows at a time
+pub fn solve_part2(input: &str) -> String {
+    let rows = match parse_triangles(input) {
+        Ok(rows) => rows,
+        Err(err) => return format!("{err}"),
+    };
+    if rows.len() % 3 != 0 {
+        return format!("{} rows don't split into groups of three", rows.len());
+    }
 
-    }).sum::<u32>();
-  
-    valid_triangles
+    rows.chunks(3)
+        .flat_map(|group| (0..3).map(move |column| [0, 1, 2].map(|row| group[row][column])))
+        .filter(|&triangle| valid_triangle(triangle))
+        .count()
+        .to_string()
 }
 
 #[cfg(test)]
@@ -47,11 +66,11 @@ mod test_day3 {
 
     #[test]
     fn test_single_triangle() {
-        let input = "4 6 32";
+        let input = [4, 6, 32];
         let expected = false;
         assert_eq!(expected, valid_triangle(input));
 
-        let input = "4 6 5";
+        let input = [4, 6, 5];
         let expected = true;
         assert_eq!(expected, valid_triangle(input));
     }
@@ -59,13 +78,26 @@ mod test_day3 {
     #[test]
     fn test_part1() {
         let input = "4 6 32\n4 6 5\n16 17 18";
-        let expected = 2;
+        let expected = "2";
         assert_eq!(expected, solve_part1(input))
     }
     #[test]
     fn test_part2() {
         let input = "4 6 32\n4 6 5\n5 17 18\n4 2 32\n5 3 1\n3 17 18";
-        let expected = 2;
+        let expected = "2";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_triangles() {
+        let error = parse_triangles("4 6 5\n4 6\n").unwrap_err();
+        assert_eq!((2, 4), (error.line, error.column));
+        assert!(parse_triangles("1 2 3 4").is_err());
+        assert!(parse_triangles("1 2 x").is_err());
+        assert_eq!(
+            "2 rows don't split into groups of three",
+            solve_part2("4 6 5\n4 6 5")
+        );
+        assert_eq!("1", solve_part1("4294967295 4294967295 4294967295"));
+    }
 }
@@ -2,14 +2,16 @@
 
 use itertools::Itertools;
 use nom::{
-    bytes::complete::tag,
-    character::complete::{self, alpha1},
+    bytes::complete::take_while1,
+    character::complete,
     combinator::opt,
-    multi::{many1, separated_list0},
+    multi::many1,
     sequence::{delimited, terminated},
     IResult,
 };
 
+use crate::utils::parse::{finish, lines, ParseError};
+
 #[derive(Debug, PartialEq, Default)]
 struct RoomEntry {
     encrypted_name: String,
@@ -52,9 +54,14 @@ impl RoomEntry {
     }
 }
 
+// Names and checksums are lowercase, decrypting only knows that alphabet
 fn parse_room_entry(input: &str) -> IResult<&str, RoomEntry> {
+    fn lowercase(input: &str) -> IResult<&str, &str> {
+        take_while1(|c: char| c.is_ascii_lowercase())(input)
+    }
+
     fn parse_encrypted_name(input: &str) -> IResult<&str, String> {
-        let (input, some_alpha) = many1(terminated(alpha1, opt(complete::char('-'))))(input)?;
+        let (input, some_alpha) = many1(terminated(lowercase, opt(complete::char('-'))))(input)?;
         Ok((input, some_alpha.join("")))
     }
 
@@ -65,7 +72,7 @@ fn parse_room_entry(input: &str) -> IResult<&str, RoomEntry> {
 
     fn parse_check_sum(input: &str) -> IResult<&str, String> {
         let (input, check_sum) =
-            delimited(complete::char('['), alpha1, complete::char(']'))(input)?;
+            delimited(complete::char('['), lowercase, complete::char(']'))(input)?;
         Ok((input, check_sum.to_owned()))
     }
 
@@ -83,21 +90,33 @@ fn parse_room_entry(input: &str) -> IResult<&str, RoomEntry> {
     ))
 }
 
-fn parse_room_entries(input: &str) -> Vec<RoomEntry> {
-    let (_, res) = separated_list0(tag("\n"), parse_room_entry)(input).unwrap_or_default();
-    res
+// An empty input is no rooms
+fn parse_room_entries(input: &str) -> Result<Vec<RoomEntry>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(parse_room_entry))
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    parse_room_entries(input)
-        .into_iter()
-        .filter(|re| re.check_validity())
-        .map(|re| re.sector_id)
-        .sum()
+pub fn solve_part1(input: &str) -> String {
+    match parse_room_entries(input) {
+        Ok(rooms) => rooms
+            .into_iter()
+            .filter(|re| re.check_validity())
+            .map(|re| re.sector_id as u64)
+            .sum::<u64>()
+            .to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let decrypted_names = parse_room_entries(input)
+    let rooms = match parse_room_entries(input) {
+        Ok(rooms) => rooms,
+        Err(err) => return format!("{err}"),
+    };
+
+    let decrypted_names = rooms
         .into_iter()
         .filter(|re| re.check_validity())
         .filter_map(|re| {
@@ -160,9 +179,18 @@ mod test_day4 {
     #[test]
     fn test_part1() {
         let input = "aaaaa-bbb-z-y-x-123[abxyz]\na-b-c-d-e-f-g-h-987[abcde]\nnot-a-real-room-404[oarel]\ntotally-real-room-200[decoy]";
-        let expected = 1514;
+        let expected = "1514";
         assert_eq!(expected, solve_part1(input))
     }
+
+    #[test]
+    fn test_bad_rooms() {
+        let error = parse_room_entries("a-b-1[ab]\nccA0[c]").unwrap_err();
+        assert_eq!((2, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2("a-b-1[ab]\nccA0[c]"));
+        assert!(parse_room_entries("a-1[a]\nb-2").is_err());
+        assert_eq!("0", solve_part1(""));
+    }
     #[test]
     fn test_part2() {
         // Not needed
@@ -2,63 +2,30 @@
 
 // https://adventofcode.com/2016/day/5
 
-use std::collections::HashSet;
-
-use itertools::{Itertools, WithPosition};
-use nom::HexDisplay;
+use crate::utils::md5_mining::{has_zero_nibbles, hex_digit, mine, nibble};
 
 pub fn solve_part1(door_id: &str) -> String {
-    (0..)
-        .map(|idx| {
-            let input = format!("{}{}", door_id, idx);
-            let digest = md5::compute(input);
-            format!("{:x}", digest)
-        })
-        .filter_map(|hash| {
-            hash.starts_with("00000")
-                .then_some(hash.chars().nth(5))
-                .flatten()
-        })
+    mine(door_id, |digest| has_zero_nibbles(digest, 5))
         .take(8)
-        .join("")
+        .map(|(_, digest)| hex_digit(nibble(&digest, 5)))
+        .collect()
 }
 
 pub fn solve_part2(door_id: &str) -> String {
-    let mut seen_positions = HashSet::new();
-
-    (0..)
-        .map(|idx| {
-            // Create hash
-            let input = format!("{}{}", door_id, idx);
-            let digest = md5::compute(input);
-            format!("{:x}", digest)
-        })
-        .filter(|hash| {
-            // Initial zeroes check
-            hash.starts_with("00000")
-        })
-        .filter_map(|hash| {
-            // Create entries of the valid ones
-            let char_pos = hash.chars().nth(5)?;
-            let character = hash.chars().nth(6)?;
+    let mut password = [None; 8];
+
+    // Only the first hash for each position counts
+    for (_, digest) in mine(door_id, |digest| {
+        has_zero_nibbles(digest, 5) && nibble(digest, 5) < 8
+    }) {
+        let position = nibble(&digest, 5) as usize;
+        password[position].get_or_insert(hex_digit(nibble(&digest, 6)));
+        if password.iter().all(Option::is_some) {
+            break;
+        }
+    }
 
-            "01234567"
-                .find(char_pos)
-                .and_then(|_| Some((char_pos, character)))
-        })
-        .filter_map(|t| {
-            // Remove duplicates
-            if seen_positions.insert(t.0) {
-                Some(t)
-            } else {
-                None
-            }
-        })
-        .take(8)
-        .inspect(|s| println!("New unique found: {:?}", s))
-        .sorted_by(|(pos, _), (pos2, _)| Ord::cmp(pos, pos2))
-        .map(|t| t.1)
-        .join("")
+    password.iter().flatten().collect()
 }
 
 #[cfg(test)]
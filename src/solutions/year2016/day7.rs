@@ -10,11 +10,13 @@ use itertools::Itertools;
 use nom::{
     branch::alt,
     character::complete::{self, alpha0, alpha1},
-    multi::many0,
+    multi::many1,
     sequence::delimited,
     IResult,
 };
 
+use crate::utils::parse::{finish, lines, ParseError};
+
 #[derive(Debug, PartialEq)]
 enum SectionKind {
     Hypernet,
@@ -27,7 +29,7 @@ struct IPv7Section<'a> {
     kind: SectionKind,
 }
 
-fn parse_ipv7_sequence(input: &str) -> Vec<IPv7Section> {
+fn parse_ipv7_sequence(input: &str) -> IResult<&str, Vec<IPv7Section<'_>>> {
     fn parse_hypernet(input: &str) -> IResult<&str, IPv7Section> {
         delimited(complete::char('['), alpha0, complete::char(']'))(input).map(|(i, s)| {
             (
@@ -52,11 +54,18 @@ fn parse_ipv7_sequence(input: &str) -> Vec<IPv7Section> {
         })
     }
 
-    let (_, v) = many0(alt((parse_supernet, parse_hypernet)))(input).unwrap_or_default();
-    v
+    many1(alt((parse_supernet, parse_hypernet)))(input)
+}
+
+// One address per line, an empty input is no addresses
+fn parse_addresses(input: &str) -> Result<Vec<Vec<IPv7Section<'_>>>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(parse_ipv7_sequence))
 }
 
-fn supports_tls(ip: &str) -> bool {
+fn supports_tls(ip: &[IPv7Section]) -> bool {
     fn has_abba(input: &str) -> bool {
         input
             .chars()
@@ -64,7 +73,7 @@ fn supports_tls(ip: &str) -> bool {
             .any(|(a, b, bb, aa)| a == aa && b == bb && a != b)
     }
 
-    let (any_hyper, any_other) = parse_ipv7_sequence(ip).into_iter().fold(
+    let (any_hyper, any_other) = ip.iter().fold(
         (false, false),
         |(any_hyper, any_other), ip_sect| match ip_sect.kind {
             SectionKind::Hypernet => (any_hyper || has_abba(ip_sect.sequence), any_other),
@@ -83,11 +92,11 @@ fn find_unique_abas(input: &str) -> HashSet<(char, char, char)> {
         .collect()
 }
 
-fn supports_ssl(ip: &str) -> bool {
+fn supports_ssl(ip: &[IPv7Section]) -> bool {
     let mut super_abas = HashSet::new();
     let mut hyper_babs = HashSet::new();
 
-    parse_ipv7_sequence(ip).iter().for_each(|section| {
+    ip.iter().for_each(|section| {
         let abas = find_unique_abas(section.sequence);
         match section.kind {
             SectionKind::Supernet => super_abas.extend(abas),
@@ -103,12 +112,18 @@ fn supports_ssl(ip: &str) -> bool {
     !super_abas.is_disjoint(&hyper_babs)
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    input.lines().filter(|ip| supports_tls(ip)).count() as u32
+pub fn solve_part1(input: &str) -> String {
+    match parse_addresses(input) {
+        Ok(ips) => ips.iter().filter(|ip| supports_tls(ip)).count().to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-    input.lines().filter(|ip| supports_ssl(ip)).count() as u32
+pub fn solve_part2(input: &str) -> String {
+    match parse_addresses(input) {
+        Ok(ips) => ips.iter().filter(|ip| supports_ssl(ip)).count().to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 #[cfg(test)]
@@ -116,11 +131,15 @@ mod test_day7 {
     use super::*;
     use SectionKind::*;
 
+    fn sections(ip: &str) -> Vec<IPv7Section<'_>> {
+        parse_ipv7_sequence(ip).unwrap().1
+    }
+
     #[test]
     fn test_parse_sequence() {
         assert_eq!(
             Vec::from([Supernet, Hypernet, Supernet]),
-            parse_ipv7_sequence("abba[mnop]qrst")
+            sections("abba[mnop]qrst")
                 .into_iter()
                 .map(|ipsec| ipsec.kind)
                 .collect::<Vec<SectionKind>>()
@@ -128,7 +147,7 @@ mod test_day7 {
 
         assert_eq!(
             Vec::from(["abba", "mnop", "qrst"]),
-            parse_ipv7_sequence("abba[mnop]qrst")
+            sections("abba[mnop]qrst")
                 .into_iter()
                 .map(|ipsec| ipsec.sequence)
                 .collect::<Vec<&str>>()
@@ -137,28 +156,28 @@ mod test_day7 {
 
     #[test]
     fn test_abba_outside() {
-        assert_eq!(true, supports_tls("abba[mnop]qrst"));
+        assert_eq!(true, supports_tls(&sections("abba[mnop]qrst")));
     }
 
     #[test]
     fn test_abba_within_larger_string() {
-        assert_eq!(true, supports_tls("ioxxoj[asdfgh]zxcvbn"));
+        assert_eq!(true, supports_tls(&sections("ioxxoj[asdfgh]zxcvbn")));
     }
 
     #[test]
     fn test_no_abba() {
-        assert_eq!(false, supports_tls("aaaa[qwer]tyui"));
+        assert_eq!(false, supports_tls(&sections("aaaa[qwer]tyui")));
     }
 
     #[test]
     fn test_abba_inside_and_outside() {
-        assert_eq!(false, supports_tls("abcd[oxxo]xyyx"));
+        assert_eq!(false, supports_tls(&sections("abcd[oxxo]xyyx")));
     }
 
     #[test]
     fn test_part1() {
         let input = "abba[mnop]qrst\nabcd[oxxo]xyyx\nioxxoj[asdfgh]zxcvbn";
-        let expected = 2;
+        let expected = "2";
         assert_eq!(expected, solve_part1(input))
     }
 
@@ -172,18 +191,27 @@ mod test_day7 {
 
     #[test]
     fn test_supports_ssl() {
-        assert_eq!(true, supports_ssl("aba[bab]xyz"));
+        assert_eq!(true, supports_ssl(&sections("aba[bab]xyz")));
     }
 
     #[test]
     fn test_not_supports_ssl() {
-        assert_eq!(false, supports_ssl("xyx[xyx]xyx"));
-        assert_eq!(false, supports_ssl("aba[cac]xyz"));
+        assert_eq!(false, supports_ssl(&sections("xyx[xyx]xyx")));
+        assert_eq!(false, supports_ssl(&sections("aba[cac]xyz")));
     }
     #[test]
     fn test_part2() {
         let input = "aba[bab]xyz\nxyx[xyx]xyx\naaa[kek]eke\nzazbz[bzb]cdb\naba[cac]xyz";
-        let expected = 3;
+        let expected = "3";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_addresses() {
+        let error = parse_addresses("abba[mnop]qrst\nab[cd").unwrap_err();
+        assert_eq!((2, 3), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1("abba[mnop]qrst\nab[cd"));
+        assert!(parse_addresses("ab1cd").is_err());
+        assert_eq!("0", solve_part2(""));
+    }
 }
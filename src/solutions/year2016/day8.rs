@@ -4,13 +4,19 @@ use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{self, newline},
-    multi::separated_list0,
+    character::complete,
+    combinator::verify,
     sequence::{preceded, separated_pair},
     IResult,
 };
 use std::{collections::BTreeSet, fmt::Display};
 
+use crate::utils::{
+    bit_grid::BitGrid,
+    ocr,
+    parse::{finish, lines, ParseError},
+};
+
 const SCREEN_WIDTH: usize = 50;
 const SCREEN_HEIGHT: usize = 6;
 
@@ -23,68 +29,48 @@ enum Command {
 
 #[derive(Clone)]
 struct Screen {
-    pixels: [[bool; SCREEN_WIDTH]; SCREEN_HEIGHT],
+    pixels: BitGrid,
 }
 
 impl Screen {
     fn new() -> Screen {
         Screen {
-            pixels: [[false; SCREEN_WIDTH]; SCREEN_HEIGHT],
+            pixels: BitGrid::new(SCREEN_WIDTH, SCREEN_HEIGHT),
         }
     }
 
     fn modify(&mut self, command: &Command) {
         match *command {
-            Command::Rect(width, height) => {
-                for row in 0..height {
-                    for col in 0..width {
-                        self.pixels[row as usize][col as usize] = true;
-                    }
-                }
-            }
-            Command::RotateRow(y, by) => {
-                let temp_vec = self.pixels[y].clone();
-
-                for i in 0..SCREEN_WIDTH {
-                    let index = (SCREEN_WIDTH + i - by) % SCREEN_WIDTH;
-                    self.pixels[y][i] = temp_vec[index];
-                }
-            }
-            Command::RotateCol(x, by) => {
-                let temp_vec = self.pixels.iter().map(|row| row[x]).collect::<Vec<bool>>();
-
-                for i in 0..SCREEN_HEIGHT {
-                    let index = (SCREEN_HEIGHT + i - by) % SCREEN_HEIGHT;
-                    self.pixels[i][x] = temp_vec[index];
-                }
-            }
+            Command::Rect(width, height) => self.pixels.fill_rect(0, 0, width, height, true),
+            Command::RotateRow(y, by) => self.pixels.rotate_row(y, by),
+            Command::RotateCol(x, by) => self.pixels.rotate_column(x, by),
         }
     }
 
     fn count_pixels(&self) -> usize {
-        self.pixels.iter().flatten().filter(|&&b| b).count()
+        self.pixels.count_ones()
     }
 }
 
 impl Display for Screen {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.pixels.iter().for_each(|pixel_row| {
-            pixel_row.iter().for_each(|&pixel| {
-                let ch = if pixel { '#' } else { ' ' };
-                write!(f, "{}", ch);
-            });
-            write!(f, "\n");
-        });
-        Ok(())
+        let rendered = self.pixels.render('#', ' ');
+        writeln!(f, "{}", rendered)
     }
 }
 
+// Commands that reach outside the screen are parse errors
 fn parse_command(input: &str) -> IResult<&str, Command> {
     fn parse_rect(input: &str) -> IResult<&str, Command> {
         preceded(
             tag("rect "),
             nom::combinator::map(
-                separated_pair(complete::u32, tag("x"), complete::u32),
+                verify(
+                    separated_pair(complete::u32, tag("x"), complete::u32),
+                    |&(width, height)| {
+                        width as usize <= SCREEN_WIDTH && height as usize <= SCREEN_HEIGHT
+                    },
+                ),
                 |(width, height)| Command::Rect(width as usize, height as usize),
             ),
         )(input)
@@ -94,14 +80,20 @@ fn parse_command(input: &str) -> IResult<&str, Command> {
         let rotate_row_parser = preceded(
             tag("row y="),
             nom::combinator::map(
-                separated_pair(complete::u32, tag(" by "), complete::u32),
+                verify(
+                    separated_pair(complete::u32, tag(" by "), complete::u32),
+                    |&(idx, _)| (idx as usize) < SCREEN_HEIGHT,
+                ),
                 |(idx, px)| Command::RotateRow(idx as usize, px as usize),
             ),
         );
         let rotate_col_parser = preceded(
             tag("column x="),
             nom::combinator::map(
-                separated_pair(complete::u32, tag(" by "), complete::u32),
+                verify(
+                    separated_pair(complete::u32, tag(" by "), complete::u32),
+                    |&(idx, _)| (idx as usize) < SCREEN_WIDTH,
+                ),
                 |(idx, px)| Command::RotateCol(idx as usize, px as usize),
             ),
         );
@@ -112,28 +104,40 @@ fn parse_command(input: &str) -> IResult<&str, Command> {
     alt((parse_rect, parse_rotate))(input)
 }
 
-fn parse_commands(input: &str) -> Vec<Command> {
-    separated_list0(newline, parse_command)(input)
-        .unwrap_or_default()
-        .1
+// A bad line anywhere fails the whole list, an empty input is no commands
+fn parse_commands(input: &str) -> Result<Vec<Command>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(parse_command))
 }
 
-pub fn solve_part1(input: &str) -> String {
+fn run_commands(input: &str) -> Result<Screen, ParseError> {
     let mut screen = Screen::new();
-    parse_commands(input)
+    parse_commands(input)?
         .iter()
         .for_each(|cmd| screen.modify(cmd));
+    Ok(screen)
+}
 
-    format!("{}", screen.count_pixels())
+pub fn solve_part1(input: &str) -> String {
+    match run_commands(input) {
+        Ok(screen) => format!("{}", screen.count_pixels()),
+        Err(err) => format!("{err}"),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let mut screen = Screen::new();
-    parse_commands(input)
-        .iter()
-        .for_each(|cmd| screen.modify(cmd));
-
-    format!("{}", screen)
+    let screen = match run_commands(input) {
+        Ok(screen) => screen,
+        Err(err) => return format!("{err}"),
+    };
+
+    // Falls back to the picture when a letter can't be read
+    match ocr::read_grid(&screen.pixels.to_grid()) {
+        Ok(letters) => letters,
+        Err(unknown) => format!("{}\n{}", unknown, screen),
+    }
 }
 
 #[cfg(test)]
@@ -153,12 +157,41 @@ mod test_day8 {
         );
     }
 
+    #[test]
+    fn test_parse_out_of_bounds() {
+        assert!(parse_command("rect 60x10").is_err());
+        assert!(parse_command("rotate row y=6 by 1").is_err());
+        assert!(parse_command("rotate column x=50 by 1").is_err());
+        assert_eq!(Command::Rect(50, 6), parse_command("rect 50x6").unwrap().1);
+    }
+
+    #[test]
+    fn test_rotate_more_than_screen() {
+        let mut screen = Screen::new();
+        screen.modify(&Command::Rect(1, 1));
+        screen.modify(&Command::RotateRow(0, SCREEN_WIDTH + 2));
+        screen.modify(&Command::RotateCol(2, SCREEN_HEIGHT + 1));
+        assert!(screen.pixels[(2, 1)]);
+        assert_eq!(1, screen.count_pixels());
+    }
+
     #[test]
     fn test_parse_command_list() {
         assert_eq!(
             vec![Command::Rect(3, 2), Command::RotateCol(5, 2)],
-            parse_commands("rect 3x2\nrotate column x=5 by 2")
-        )
+            parse_commands("rect 3x2\nrotate column x=5 by 2").unwrap()
+        );
+        assert_eq!(Vec::<Command>::new(), parse_commands("").unwrap());
+    }
+
+    #[test]
+    fn test_bad_line_fails_everything() {
+        // Nothing after the bad line is quietly dropped
+        let input = "rect 3x2\nrotate row y=9 by 1\nrect 1x1";
+        let error = parse_commands(input).unwrap_err();
+        assert_eq!((2, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1(input));
+        assert_eq!(error.to_string(), solve_part2(input));
     }
 
     #[test]
@@ -167,4 +200,14 @@ mod test_day8 {
         let expected = "6";
         assert_eq!(expected, solve_part1(input))
     }
+
+    #[test]
+    fn test_part2() {
+        // An L, with the bottom row moved down from the top
+        let input = "rect 4x1\nrotate column x=1 by 5\nrotate column x=2 by 5\nrotate column x=3 by 5\nrect 1x6";
+        assert_eq!("L", solve_part2(input));
+
+        let unreadable = solve_part2("rect 2x2");
+        assert!(unreadable.starts_with("Unknown glyph at letter 0:\n##..."));
+    }
 }
@@ -1,6 +1,11 @@
 #![allow(unused)]
 
-use nom::{branch, bytes::complete, character, combinator, sequence, IResult};
+use nom::{branch, bytes::complete, character, combinator, multi, sequence, IResult};
+
+use crate::utils::{
+    memo::Memo,
+    parse::{finish, ParseError},
+};
 
 #[derive(Debug, PartialEq)]
 enum CompressionSequence<'a> {
@@ -24,35 +29,101 @@ fn parse_raw(input: &str) -> IResult<&str, CompressionSequence> {
     combinator::map(complete::take_till1(|c| c == '('), CompressionSequence::Raw)(input)
 }
 
-fn decompress_size(input: &str) -> u32 {
-    combinator::iterator(input, branch::alt((parse_repeated, parse_raw)))
-        .map(|cs| match cs {
-            CompressionSequence::Raw(s) => s.len() as u32,
-            CompressionSequence::Repeated(r, s) => r * s.len() as u32,
-        })
-        .sum()
+// A `(` that doesn't start a whole marker is an error
+fn parse_sequences(input: &str) -> Result<Vec<CompressionSequence<'_>>, ParseError> {
+    finish(
+        input,
+        multi::many0(branch::alt((parse_repeated, parse_raw))),
+    )
 }
 
-fn decompress_size_v2(input: &str) -> u128 {
-    combinator::iterator(input, branch::alt((parse_repeated, parse_raw)))
+fn decompress_size(input: &str) -> Result<u64, ParseError> {
+    // A marker can only repeat text that is in the input, so this stays far below u64::MAX
+    Ok(parse_sequences(input)?
+        .into_iter()
         .map(|cs| match cs {
-            CompressionSequence::Raw(s) => s.len() as u128,
-            CompressionSequence::Repeated(r, s) => (r as u128) * decompress_size_v2(s),
+            CompressionSequence::Raw(s) => s.len() as u64,
+            CompressionSequence::Repeated(r, s) => r as u64 * s.len() as u64,
         })
-        .sum()
+        .sum())
+}
+
+// Markers inside a repeated section are parsed when it is decompressed, so their errors
+// are placed within that section
+fn decompress_size_v2(input: &str) -> Result<u128, String> {
+    // The same section often turns up inside several markers
+    Memo::<String, Result<u128, String>>::new("decompress_size_v2").solve(
+        input,
+        |size, input: &str| {
+            parse_sequences(input)
+                .map_err(|err| format!("{err}"))?
+                .into_iter()
+                .try_fold(0u128, |total, cs| {
+                    let length = match cs {
+                        CompressionSequence::Raw(s) => Some(s.len() as u128),
+                        CompressionSequence::Repeated(r, s) => size.call(s)?.checked_mul(r as u128),
+                    };
+                    length
+                        .and_then(|length| total.checked_add(length))
+                        .ok_or_else(|| "The decompressed length overflows a u128".to_string())
+                })
+        },
+    )
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    decompress_size(input) as u32
+pub fn solve_part1(input: &str) -> String {
+    match decompress_size(input) {
+        Ok(size) => size.to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
-pub fn solve_part2(input: &str) -> u128 {
-    decompress_size_v2(input) as u128
+pub fn solve_part2(input: &str) -> String {
+    match decompress_size_v2(input) {
+        Ok(size) => size.to_string(),
+        Err(err) => err,
+    }
 }
 
 #[cfg(test)]
 mod test_day9 {
     use super::*;
+    use crate::utils::prop::check;
+
+    #[test]
+    fn prop_v2_equals_v1_without_nested_markers() {
+        // Segments of text, repeated by a marker unless the repeat count is zero
+        check(
+            "decompress_size_v2 equals decompress_size when no markers are nested",
+            |rng| {
+                (0..rng.index(6))
+                    .map(|_| {
+                        let repeat = if rng.chance(1, 2) {
+                            0
+                        } else {
+                            rng.below(10) as u32
+                        };
+                        let text = (0..rng.range(1..8))
+                            .map(|_| (b'A' + rng.below(26) as u8) as char)
+                            .collect::<String>();
+                        (repeat, text)
+                    })
+                    .collect::<Vec<(u32, String)>>()
+            },
+            |segments| {
+                let input = segments
+                    .iter()
+                    .map(|(repeat, text)| match repeat {
+                        0 => text.clone(),
+                        _ => format!("({}x{repeat}){text}", text.len()),
+                    })
+                    .collect::<String>();
+
+                let v1 = decompress_size(&input).map(u128::from);
+                v1.map_err(|err| format!("{err}")) == decompress_size_v2(&input)
+            },
+        )
+    }
 
     #[test]
     fn test_parse_repeated() {
@@ -82,21 +153,39 @@ mod test_day9 {
     fn test_decompress() {
         let input = "ADVENT";
         let expected = 6;
-        assert_eq!(expected, decompress_size(input));
+        assert_eq!(Ok(expected), decompress_size(input));
 
         let input = "X(8x2)(3x3)ABCY";
         let expected = 18;
-        assert_eq!(expected, decompress_size(input));
+        assert_eq!(Ok(expected), decompress_size(input));
     }
 
     #[test]
     fn test_decompress_v2() {
         let input = "ADVENT";
         let expected = 6;
-        assert_eq!(expected, decompress_size_v2(input));
+        assert_eq!(Ok(expected), decompress_size_v2(input));
 
         let input = "X(8x2)(3x3)ABCY";
         let expected = 20;
-        assert_eq!(expected, decompress_size_v2(input));
+        assert_eq!(Ok(expected), decompress_size_v2(input));
+    }
+
+    #[test]
+    fn test_bad_markers() {
+        // The marker wants more characters than there are
+        let error = decompress_size("AB(5x2)XY").unwrap_err();
+        assert_eq!((1, 3), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1("AB(5x2)XY"));
+        assert!(decompress_size_v2("(3x2)(1x").is_err());
+
+        // Every level multiplies by 2^32 - 1
+        let nested = (0..4).fold("ABC".to_string(), |inner, _| {
+            format!("({}x4294967295){inner}", inner.len())
+        });
+        assert_eq!(
+            Err("The decompressed length overflows a u128".to_string()),
+            decompress_size_v2(&nested)
+        );
     }
 }
@@ -1,3 +1,4 @@
+pub mod assembunny;
 pub mod day1;
 pub mod day2;
 pub mod day3;
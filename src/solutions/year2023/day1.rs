@@ -3,24 +3,33 @@
 use itertools::enumerate;
 use nom::FindSubstring;
 
-pub fn solve_part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| {
-            let mut digits = line.chars().filter_map(|char| char.to_digit(10));
+fn calibration_value(mut digits: impl Iterator<Item = u32>) -> Option<u32> {
+    let first_digit = digits.next()?;
+    let last_digit = digits.last().unwrap_or(first_digit);
 
-            let first_digit = digits.next();
-            let last_digit = digits.last();
+    Some(first_digit * 10 + last_digit)
+}
 
-            let first_digit = first_digit.expect("There needs to be at least one number");
-            let last_digit = last_digit.unwrap_or(first_digit);
+// A line without any digit has no calibration value, so the document is invalid
+fn calibration_sum(input: &str, value: impl Fn(&str) -> Option<u32>) -> String {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| value(line).ok_or(i + 1))
+        .sum::<Result<u32, usize>>()
+        .map_or_else(
+            |line| format!("No digit on line {line}"),
+            |sum| sum.to_string(),
+        )
+}
 
-            first_digit * 10 + last_digit
-        })
-        .sum()
+pub fn solve_part1(input: &str) -> String {
+    calibration_sum(input, |line| {
+        calibration_value(line.chars().filter_map(|char| char.to_digit(10)))
+    })
 }
 
-pub fn solve_part2(input: &str) -> u32 {
+pub fn solve_part2(input: &str) -> String {
     fn find_digit(rline: &str) -> Option<u32> {
         let string_digits = [
             "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
@@ -39,23 +48,12 @@ pub fn solve_part2(input: &str) -> u32 {
         None
     }
 
-    input
-        .lines()
-        .map(|line| {
-            let mut digits = (0..line.len()).filter_map(|i| {
-                let rline = &line[i..];
-                find_digit(rline)
-            });
-
-            let first_digit = digits.next();
-            let last_digit = digits.last();
-
-            let first_digit = first_digit.expect("There needs to be at least one number");
-            let last_digit = last_digit.unwrap_or(first_digit);
-
-            first_digit * 10 + last_digit
-        })
-        .sum()
+    calibration_sum(input, |line| {
+        calibration_value(
+            line.char_indices()
+                .filter_map(|(i, _)| find_digit(&line[i..])),
+        )
+    })
 }
 
 #[cfg(test)]
@@ -65,13 +63,26 @@ mod test_day1 {
     #[test]
     fn test_part1() {
         let input = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
-        let expected = 142;
+        let expected = "142";
         assert_eq!(expected, solve_part1(input))
     }
+    #[test]
+    fn test_line_without_digits_is_an_error() {
+        assert_eq!("No digit on line 2", solve_part1("1x2\nabc\n3"));
+        assert_eq!("No digit on line 1", solve_part2("abc\n1x2"));
+        assert_eq!("0", solve_part1(""));
+    }
+
+    #[test]
+    fn test_part2_non_ascii() {
+        let input = "é1🎄twoñ";
+        assert_eq!("12", solve_part2(input))
+    }
+
     #[test]
     fn test_part2() {
         let input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen";
-        let expected = 281;
+        let expected = "281";
         assert_eq!(expected, solve_part2(input))
     }
 }
@@ -1,44 +1,13 @@
 #![allow(unused)]
 
-use std::collections::{HashMap, HashSet};
-
-use itertools::{iterate, unfold, Itertools};
-use nom::{
-    character::complete::{anychar, multispace0},
-    multi::many1,
-    sequence::terminated,
-    IResult, Map, Parser,
+use crate::utils::{
+    direction::Direction,
+    geometry::interior_points,
+    grid::Grid,
+    parse::{finish, grid},
+    point::Point2,
+    search::bfs_distances,
 };
-use nom_locate::LocatedSpan;
-use num::Integer;
-
-type Span<'a> = LocatedSpan<&'a str>;
-
-#[derive(Debug, PartialEq, Clone)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-impl Direction {
-    fn opposite(&self) -> Direction {
-        match self {
-            Direction::North => Direction::South,
-            Direction::East => Direction::West,
-            Direction::South => Direction::North,
-            Direction::West => Direction::East,
-        }
-    }
-}
-
-#[derive(Debug, PartialEq)]
-enum PipeType {
-    CornerUp,
-    CornerDown,
-    Vertical,
-    Horizontal,
-}
 
 #[derive(Debug, Clone)]
 struct Pipe {
@@ -49,27 +18,6 @@ impl Pipe {
     fn has_connection(&self, from: &Direction) -> bool {
         [&self.end1, &self.end2].contains(&from)
     }
-
-    fn get_type(&self) -> PipeType {
-        match (self.end1.clone(), self.end2.clone()) {
-            (Direction::North, Direction::North) => panic!(),
-            (Direction::East, Direction::East) => panic!(),
-            (Direction::South, Direction::South) => panic!(),
-            (Direction::West, Direction::West) => panic!(),
-            (Direction::North, Direction::South) => PipeType::Vertical,
-            (Direction::South, Direction::North) => PipeType::Vertical,
-            (Direction::East, Direction::West) => PipeType::Horizontal,
-            (Direction::West, Direction::East) => PipeType::Horizontal,
-            (Direction::North, Direction::West) => PipeType::CornerUp,
-            (Direction::North, Direction::East) => PipeType::CornerUp,
-            (Direction::East, Direction::North) => PipeType::CornerUp,
-            (Direction::West, Direction::North) => PipeType::CornerUp,
-            (Direction::South, Direction::East) => PipeType::CornerDown,
-            (Direction::South, Direction::West) => PipeType::CornerDown,
-            (Direction::East, Direction::South) => PipeType::CornerDown,
-            (Direction::West, Direction::South) => PipeType::CornerDown,
-        }
-    }
 }
 
 enum MapElement {
@@ -78,49 +26,32 @@ enum MapElement {
     Empty,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct Coordinate {
-    x: usize,
-    y: usize,
+// Signed, so stepping off the top or left of the grid is simply a negative coordinate
+type Coordinate = Point2<i64>;
+
+fn travel(pos: Coordinate, dir: Direction) -> (Direction, Coordinate) {
+    (dir.turn_around(), pos + dir.into())
 }
 
-impl Coordinate {
-    fn travel(&self, dir: &Direction) -> (Direction, Coordinate) {
-        (
-            dir.opposite(),
-            match dir {
-                Direction::North => Coordinate {
-                    x: self.x,
-                    y: self.y - 1,
-                },
-                Direction::East => Coordinate {
-                    x: self.x + 1,
-                    y: self.y,
-                },
-                Direction::South => Coordinate {
-                    x: self.x,
-                    y: self.y + 1,
-                },
-                Direction::West => Coordinate {
-                    x: self.x - 1,
-                    y: self.y,
-                },
-            },
-        )
-    }
+// The grid cell of a coordinate that is known to be on the grid
+fn cell(pos: Coordinate) -> (usize, usize) {
+    pos.try_cast::<usize>()
+        .expect("Should be on the grid")
+        .into()
 }
 
-fn parse_start_and_pipes(input: Span) -> (Coordinate, HashMap<Coordinate, Pipe>) {
-    fn parse_map_element(input: Span) -> IResult<Span, (Coordinate, MapElement)> {
-        use Direction::*;
+type Pipes = Grid<Option<Pipe>>;
 
-        let pos = Coordinate {
-            x: input.get_column(),
-            y: input.location_line() as usize,
-        };
-        let (input, c) = anychar(input)?;
+fn pipe_at<'a>(pipes: &'a Pipes, pos: &Coordinate) -> Option<&'a Pipe> {
+    let pos = pos.try_cast::<usize>()?;
+    pipes.get(pos.x, pos.y).and_then(Option::as_ref)
+}
 
-        let elem = match c {
+fn parse_start_and_pipes(input: &str) -> Result<(Coordinate, Pipes), String> {
+    fn parse_map_element(c: char) -> Option<MapElement> {
+        use Direction::*;
+
+        let element = match c {
             '-' => MapElement::Pipe(Pipe {
                 end1: West,
                 end2: East,
@@ -147,60 +78,74 @@ fn parse_start_and_pipes(input: Span) -> (Coordinate, HashMap<Coordinate, Pipe>)
             }),
             'S' => MapElement::Start,
             '.' => MapElement::Empty,
-            _ => panic!("Unknown character"),
+            _ => return None,
         };
-
-        Ok((input, (pos, elem)))
+        Some(element)
     }
 
-    let (_, elements) =
-        many1(terminated(parse_map_element, multispace0))(input).expect("Parsing works");
-
-    let mut map = HashMap::new();
-    let mut start = None;
+    let elements = finish(input, grid(parse_map_element)).map_err(|err| format!("{err}"))?;
 
-    elements.into_iter().for_each(|(pos, elem)| match elem {
-        MapElement::Pipe(pipe) => {
-            map.insert(pos, pipe);
-        }
-        MapElement::Start => {
-            start = Some(pos);
-        }
-        MapElement::Empty => {}
+    let (x, y) = elements
+        .position(|elem| matches!(elem, MapElement::Start))
+        .ok_or("There is no S on the map")?;
+    let pipes = elements.map(|elem| match elem {
+        MapElement::Pipe(pipe) => Some(pipe.clone()),
+        _ => None,
     });
 
-    (start.expect("Should find start"), map)
+    Ok((Coordinate::new(x as i64, y as i64), pipes))
 }
 
-pub fn solve_part1(input: &str) -> String {
-    let (start, map) = parse_start_and_pipes(Span::new(input));
-
-    // Assumption that first step is to the east. Works for puzzle input.
-    let begin = start.travel(&Direction::East);
-
-    let loop_length = iterate(begin, |(came_from, pos)| {
-        let Some(pipe) = map.get(pos) else {
-            // Need this hack since start is not in the hashmap
-            return (Direction::North, start.clone());
-        };
-
-        let next_dir = if (pipe.end1 == *came_from) {
-            pipe.end2.clone()
-        } else {
-            pipe.end1.clone()
-        };
-
-        pos.travel(&next_dir)
+// Both positions a pipe connects to
+fn connected(map: &Pipes, pos: Coordinate) -> Vec<Coordinate> {
+    pipe_at(map, &pos).map_or(Vec::new(), |pipe| {
+        vec![travel(pos, pipe.end1).1, travel(pos, pipe.end2).1]
     })
-    .take_while(|(_, pos)| *pos != start)
-    .count();
+}
 
-    // Add two because we don't count the last step or the first..
-    let half_loop = (loop_length + 2) / 2;
+// Like parse_start_and_pipes, with the start pipe filled in
+fn parse_loop(input: &str) -> Result<(Coordinate, Pipes), String> {
+    let (start, mut map) = parse_start_and_pipes(input)?;
+    let pipe = start_pipe(&start, &map).ok_or("The start doesn't connect to two pipes")?;
+    map[cell(start)] = Some(pipe);
+    Ok((start, map))
+}
+
+pub fn solve_part1(input: &str) -> String {
+    let (start, map) = match parse_loop(input) {
+        Ok(parsed) => parsed,
+        Err(err) => return err,
+    };
+    let distances = bfs_distances(start, |&pos| connected(&map, pos));
+
+    // The loop is walked both ways at once, so the farthest point is halfway round
+    let half_loop = distances.values().max().expect("The start is in the loop");
     half_loop.to_string()
 }
 
-fn start_pipe(pos: &Coordinate, map: &HashMap<Coordinate, Pipe>) -> Pipe {
+// The pipes of the loop in walking order, from the start all the way round. Every pipe
+// has to connect back to the one before it, so the walk can't wander off the loop.
+fn walk_loop(start: Coordinate, map: &Pipes) -> Result<Vec<Coordinate>, String> {
+    let mut path = vec![start];
+    let mut previous = start;
+    let mut pos = connected(map, start)[0];
+    while pos != start {
+        let ends = connected(map, pos);
+        if !ends.contains(&previous) {
+            return Err(format!("The loop is broken at ({}, {})", pos.x, pos.y));
+        }
+        let next = ends
+            .into_iter()
+            .find(|&next| next != previous)
+            .expect("The ends of a pipe are different");
+        path.push(pos);
+        previous = pos;
+        pos = next;
+    }
+    Ok(path)
+}
+
+fn start_pipe(pos: &Coordinate, map: &Pipes) -> Option<Pipe> {
     let mut neighs = [
         Direction::East,
         Direction::West,
@@ -208,88 +153,26 @@ fn start_pipe(pos: &Coordinate, map: &HashMap<Coordinate, Pipe>) -> Pipe {
         Direction::South,
     ]
     .into_iter()
-    .map(|dir| pos.travel(&dir))
+    .map(|dir| travel(*pos, dir))
     .flat_map(|(from, coord)| {
-        map.get(&coord)
-            .and_then(|p| p.has_connection(&from).then_some(from.opposite()))
+        pipe_at(map, &coord).and_then(|p| p.has_connection(&from).then_some(from.turn_around()))
     });
 
-    Pipe {
-        end1: neighs
-            .next()
-            .expect("Start point has two compatable neighbors"),
-        end2: neighs
-            .next()
-            .expect("Start point has two compatable neighbors"),
-    }
+    Some(Pipe {
+        end1: neighs.next()?,
+        end2: neighs.next()?,
+    })
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let (start, mut map) = parse_start_and_pipes(Span::new(input));
-
-    // Figure out the type of the start pipe
-    let first_pipe = start_pipe(&start, &map);
-    let start_dir = first_pipe.end1.clone();
-    map.insert(start.clone(), first_pipe);
-
-    // Find the loop of pipes and create a new hashmap
-    let pipes = iterate((start_dir, start), |(came_from, pos)| {
-        let pipe = map.get(pos).expect("should find pipe");
-
-        let next_dir = if (pipe.end1 == *came_from) {
-            &pipe.end2
-        } else {
-            &pipe.end1
-        };
-
-        pos.travel(next_dir)
-    })
-    .map(|(_, pos)| {
-        (
-            pos,
-            map.get(&pos)
-                .and_then(|pipe| Some(pipe.get_type()))
-                .expect("Will find a pipe"),
-        )
-    })
-    .skip(1)
-    .take_while_inclusive(|(pos, _)| *pos != start)
-    .collect::<HashMap<Coordinate, PipeType>>();
-
-    // Loop through the indices of the input line by line and count inside coords
-    input
-        .lines()
-        .enumerate()
-        .map(|(y, line)| {
-            let (in_count, _, _) = line.chars().enumerate().fold(
-                (0_u32, 0_u32, None),
-                |(in_count, pipe_count, last_corner), (x, _)| {
-                    let coord = Coordinate { x: x + 1, y: y + 1 };
-
-                    let pipe_type = pipes.get(&coord);
-
-                    match pipe_type {
-                        Some(PipeType::Vertical) => (in_count, pipe_count + 1, last_corner),
-                        Some(PipeType::Horizontal) => (in_count, pipe_count, last_corner),
-                        Some(corner) if last_corner.is_none() => {
-                            (in_count, pipe_count, Some(corner))
-                        }
-                        Some(corner) => {
-                            if corner == last_corner.unwrap() {
-                                (in_count, pipe_count, last_corner)
-                            } else {
-                                (in_count, pipe_count + 1, last_corner)
-                            }
-                        }
-                        None if pipe_count.is_odd() => (in_count + 1, pipe_count, last_corner),
-                        None => (in_count, pipe_count, last_corner),
-                    }
-                },
-            );
-            dbg!(in_count)
-        })
-        .sum::<u32>()
-        .to_string()
+    let path = match parse_loop(input).and_then(|(start, map)| walk_loop(start, &map)) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+
+    // Every pipe of the loop is a corner of the polygon, the enclosed tiles are the
+    // lattice points strictly inside it
+    interior_points(&path).to_string()
 }
 
 #[cfg(test)]
@@ -349,4 +232,13 @@ L--J.L7...LJS7F-7L7.
 
         assert_eq!(output, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_maps() {
+        let error = solve_part1(".S-7.\n.|x|.");
+        assert!(error.starts_with("line 2, column 3"), "{error}");
+        assert_eq!("There is no S on the map", solve_part2(".F-7.\n.L-J."));
+        assert_eq!("The start doesn't connect to two pipes", solve_part1("S-."));
+        assert_eq!("The loop is broken at (1, 1)", solve_part2("S7.\n|--\nL-J"));
+    }
 }
@@ -1,78 +1,81 @@
 #![allow(unused)]
 
 use itertools::Itertools;
-use std::collections::{BTreeMap, BTreeSet};
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
-struct Star {
-    x: usize,
-    y: usize,
-}
+use crate::utils::{
+    grid::Grid,
+    parse::{finish, grid, ParseError},
+    point::Point2,
+};
+
+type Star = Point2<usize>;
 
-impl Star {
-    fn distance_to(&self, other: &Star) -> usize {
-        (self.x as i64 - other.x as i64).unsigned_abs() as usize
-            + (self.y as i64 - other.y as i64).unsigned_abs() as usize
+// An empty input is an empty sky
+fn parse_sky(input: &str) -> Result<Grid<bool>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Grid::new(0, 0, false));
     }
+    finish(
+        input,
+        grid(|c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }),
+    )
 }
 
-fn parse_sky(input: &str) -> BTreeSet<Star> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.char_indices().flat_map(move |(x, c)| match c {
-                '#' => Some(Star { x, y }),
-                _ => None,
-            })
+// How far every row or column moves once all empty lines before it have grown
+fn expansion_offsets<'a>(
+    lines: impl Iterator<Item = impl IntoIterator<Item = &'a bool>>,
+    expansion: usize,
+) -> Vec<usize> {
+    lines
+        .scan(0, |offset, line| {
+            let current = *offset;
+            if !line.into_iter().any(|&star| star) {
+                *offset += expansion;
+            }
+            Some(current)
         })
-        .collect::<BTreeSet<Star>>()
+        .collect()
 }
 
-fn calc_distance(input: &str, expansion: usize) -> usize {
-    let stars = parse_sky(input);
-    let mut visited_x = BTreeSet::new();
-    let mut visited_y = BTreeSet::new();
-
-    stars.iter().for_each(|Star { x, y }| {
-        visited_x.insert(x);
-        visited_y.insert(y);
-    });
+fn calc_distance(input: &str, expansion: usize) -> Result<u128, ParseError> {
+    let sky = parse_sky(input)?;
 
     let expansion = expansion - 1;
-    let expansion_x = visited_x
-        .into_iter()
-        .enumerate()
-        .map(|(i, x)| (*x, (*x - i) * expansion))
-        .collect::<BTreeMap<usize, usize>>();
-    let expansion_y = visited_y
-        .into_iter()
-        .enumerate()
-        .map(|(i, y)| (*y, (*y - i) * expansion))
-        .collect::<BTreeMap<usize, usize>>();
+    let expansion_x = expansion_offsets(sky.columns(), expansion);
+    let expansion_y = expansion_offsets(sky.rows(), expansion);
 
-    let expanded_stars = stars
-        .into_iter()
-        .map(|Star { x, y }| Star {
-            x: x + expansion_x.get(&x).unwrap(),
-            y: y + expansion_y.get(&y).unwrap(),
-        })
+    let expanded_stars = sky
+        .iter()
+        .filter(|(_, &star)| star)
+        .map(|((x, y), _)| Star::new(x + expansion_x[x], y + expansion_y[y]))
         .collect_vec();
 
-    expanded_stars
+    // Every pair is counted both ways. The sum of all of them can outgrow a usize on a
+    // big enough sky.
+    Ok(expanded_stars
         .iter()
         .cartesian_product(expanded_stars.iter())
-        .map(|(s1, s2)| s1.distance_to(s2))
-        .sum::<usize>()
-        / 2
+        .map(|(s1, s2)| s1.manhattan(*s2) as u128)
+        .sum::<u128>()
+        / 2)
 }
 
 pub fn solve_part1(input: &str) -> String {
-    calc_distance(input, 2).to_string()
+    match calc_distance(input, 2) {
+        Ok(distance) => distance.to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
-    calc_distance(input, 1_000_000).to_string()
+    match calc_distance(input, 1_000_000) {
+        Ok(distance) => distance.to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 #[cfg(test)]
@@ -107,7 +110,7 @@ mod test_day11 {
 .......#..
 #...#.....";
         let expected = 1030;
-        assert_eq!(expected, calc_distance(input, 10))
+        assert_eq!(Ok(expected), calc_distance(input, 10))
     }
 
     #[test]
@@ -123,6 +126,16 @@ mod test_day11 {
 .......#..
 #...#.....";
         let expected = 8410;
-        assert_eq!(expected, calc_distance(input, 100))
+        assert_eq!(Ok(expected), calc_distance(input, 100))
+    }
+
+    #[test]
+    fn test_bad_sky() {
+        let error = calc_distance("#..\n.x.", 2).unwrap_err();
+        assert_eq!((2, 2), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2("#..\n.x."));
+        assert!(calc_distance("#..\n..", 2).is_err());
+        assert!(solve_part1("\n\0").starts_with("line 1, column 1"));
+        assert_eq!("0", solve_part1("\n"));
     }
 }
@@ -1,77 +1,92 @@
 #![allow(unused)]
 
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::u32,
-    combinator::map,
-    multi::separated_list1,
-    sequence::{delimited, preceded, terminated},
-    IResult,
-};
+use pattern_derive::FromPattern;
 
+#[derive(Clone, Copy, FromPattern)]
 enum CubeCount {
+    #[pattern("{0} blue")]
     Blue(u32),
+    #[pattern("{0} green")]
     Green(u32),
+    #[pattern("{0} red")]
     Red(u32),
 }
 
-fn parse_game(input: &str) -> IResult<&str, (u32, Vec<CubeCount>)> {
-    let (input, game_id) = delimited(tag("Game "), u32, tag(": "))(input)?;
+#[derive(FromPattern)]
+#[pattern("{0:, }")]
+struct Draw(Vec<CubeCount>);
 
-    let cube_draw = separated_list1(
-        tag(", "),
-        alt((
-            map(terminated(u32, tag(" blue")), CubeCount::Blue),
-            map(terminated(u32, tag(" red")), CubeCount::Red),
-            map(terminated(u32, tag(" green")), CubeCount::Green),
-        )),
-    );
+#[derive(FromPattern)]
+#[pattern("Game {id}: {draws:; }")]
+struct Game {
+    id: u32,
+    draws: Vec<Draw>,
+}
 
-    let (input, cube_draws) = separated_list1(tag("; "), cube_draw)(input)?;
+fn parse_game(input: &str) -> Result<(u32, Vec<CubeCount>), String> {
+    let game = input.parse::<Game>()?;
+    let cube_counts = game.draws.into_iter().flat_map(|Draw(counts)| counts);
 
-    Ok((input, (game_id, cube_draws.into_iter().flatten().collect())))
+    Ok((game.id, cube_counts.collect()))
 }
 
-fn is_possible(input: &str) -> Option<u32> {
-    let (_, (game_id, cube_draws)) = parse_game(input).expect("Should be able to parse");
+// The first line that isn't a game fails the whole list
+fn parse_games(input: &str) -> Result<Vec<(u32, Vec<CubeCount>)>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_game(line).map_err(|error| format!("line {}: {error}", i + 1)))
+        .collect()
+}
 
+fn is_possible((game_id, cube_draws): &(u32, Vec<CubeCount>)) -> Option<u32> {
     cube_draws
-        .into_iter()
-        .all(|cube_count| match cube_count {
+        .iter()
+        .all(|&cube_count| match cube_count {
             CubeCount::Blue(n) => n <= 14,
             CubeCount::Green(n) => n <= 13,
             CubeCount::Red(n) => n <= 12,
         })
-        .then_some(game_id)
+        .then_some(*game_id)
 }
 
-fn minimum_counts(input: &str) -> (u32, u32, u32) {
-    let (_, (game_id, cube_draws)) = parse_game(input).expect("Should be able to parse");
-
+fn minimum_counts((_, cube_draws): &(u32, Vec<CubeCount>)) -> (u32, u32, u32) {
     let (mut reds, mut greens, mut blues) = (0, 0, 0);
-    cube_draws
-        .into_iter()
-        .for_each(|cube_count| match cube_count {
-            CubeCount::Blue(n) if n > blues => blues = n,
-            CubeCount::Green(n) if n > greens => greens = n,
-            CubeCount::Red(n) if n > reds => reds = n,
-            _ => (),
-        });
+    cube_draws.iter().for_each(|&cube_count| match cube_count {
+        CubeCount::Blue(n) if n > blues => blues = n,
+        CubeCount::Green(n) if n > greens => greens = n,
+        CubeCount::Red(n) if n > reds => reds = n,
+        _ => (),
+    });
 
     (reds, greens, blues)
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    input.lines().filter_map(is_possible).sum()
+// Ids and counts are u32, so sums go in a u64 and the power of a game in a u128
+pub fn solve_part1(input: &str) -> String {
+    match parse_games(input) {
+        Ok(games) => games
+            .iter()
+            .filter_map(is_possible)
+            .map(u64::from)
+            .sum::<u64>()
+            .to_string(),
+        Err(error) => error,
+    }
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-    input
-        .lines()
+pub fn solve_part2(input: &str) -> String {
+    let games = match parse_games(input) {
+        Ok(games) => games,
+        Err(error) => return error,
+    };
+
+    games
+        .iter()
         .map(minimum_counts)
-        .map(|(r, g, b)| r * g * b)
-        .sum()
+        .map(|(r, g, b)| r as u128 * g as u128 * b as u128)
+        .sum::<u128>()
+        .to_string()
 }
 
 #[cfg(test)]
@@ -83,8 +98,8 @@ mod test_day2 {
         let possible = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
         let impossible = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
 
-        assert_eq!(Some(1), is_possible(possible));
-        assert_eq!(None, is_possible(impossible));
+        assert_eq!(Some(1), is_possible(&parse_game(possible).unwrap()));
+        assert_eq!(None, is_possible(&parse_game(impossible).unwrap()));
     }
 
     #[test]
@@ -94,7 +109,7 @@ Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let expected = 8;
+        let expected = "8";
         assert_eq!(expected, solve_part1(input))
     }
     #[test]
@@ -104,7 +119,22 @@ Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-        let expected = 2286;
+        let expected = "2286";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_games() {
+        let input = "Game 1: 3 blue\nGame 2: 3 purple";
+        let error = parse_games(input).map(|_| ()).unwrap_err();
+        assert!(error.starts_with("line 2: "), "{error}");
+        assert_eq!(error, solve_part1(input));
+        assert_eq!("0", solve_part1(""));
+
+        let input = "Game 1: 4294967295 red, 4294967295 green, 4294967295 blue";
+        assert_eq!(
+            (4294967295u128 * 4294967295 * 4294967295).to_string(),
+            solve_part2(input)
+        );
+    }
 }
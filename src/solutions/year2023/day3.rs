@@ -1,24 +1,11 @@
 #![allow(unused)]
 
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    default,
-    rc::Rc,
-    usize,
-};
-
 use itertools::Itertools;
-use nom::{
-    self,
-    branch::alt,
-    character::complete::{anychar, digit0},
-    multi::{many0, many1, many1_count},
-};
-use nom::{
-    character::{self, complete::digit1},
-    combinator::map,
-    multi::many0_count,
-    IResult,
+
+use crate::utils::{
+    grid::Grid,
+    parse::{char_grid, finish},
+    union_find::UnionFind,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,170 +17,112 @@ struct Number {
 }
 
 impl Number {
-    fn neighbours(&self) -> Vec<Pos> {
-        ((self.start_x as i32 - 1)..=(self.end_x as i32 + 1))
-            .map(|x| {
-                let y = self.y as i32;
-                [Pos::new(x, y - 1), Pos::new(x, y), Pos::new(x, y + 1)]
-            })
-            .flatten()
-            .collect()
-    }
-}
-
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
-struct Pos {
-    x: i32,
-    y: i32,
-}
-impl Pos {
-    fn new(x: i32, y: i32) -> Pos {
-        Pos { y, x }
+    // Positions around the number on the schematic, including the digits themselves
+    fn neighbours<'a>(
+        &self,
+        schematic: &'a Grid<char>,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let y = self.y;
+        (self.start_x..=self.end_x).flat_map(move |x| schematic.neighbours8(x, y))
     }
 }
 
-#[derive(Debug)]
-struct Symbol {
-    y: i32,
-    x: i32,
-    value: char,
+fn is_symbol(c: char) -> bool {
+    c != '.' && !c.is_ascii_digit()
 }
 
-impl Symbol {
-    fn neighbours(&self) -> Vec<Pos> {
-        ((self.x as i32 - 1)..=(self.x as i32 + 1))
-            .map(|x| {
-                let y = self.y as i32;
-                [Pos::new(x, y - 1), Pos::new(x, y), Pos::new(x, y + 1)]
-            })
-            .flatten()
-            .collect()
+// Digits next to each other on a row make up one number, which has to fit in a u32
+fn find_numbers(schematic: &Grid<char>) -> Result<Vec<Number>, String> {
+    let mut digits = UnionFind::new();
+    for ((x, y), _) in schematic.iter().filter(|(_, c)| c.is_ascii_digit()) {
+        digits.insert((x, y));
+        if x > 0 && schematic[(x - 1, y)].is_ascii_digit() {
+            digits.union((x - 1, y), (x, y));
+        }
     }
-}
-
-#[derive(Debug, PartialEq)]
-enum TokenType {
-    Number(usize),
-    Symbol(char),
-    Empty,
-}
 
-#[derive(Debug, PartialEq)]
-struct ParseToken {
-    token_type: TokenType,
-    size: usize,
+    // The digits of a number were inserted left to right
+    digits
+        .components()
+        .map(|cells| {
+            let (start_x, y) = *cells[0];
+            let (end_x, _) = **cells.last().expect("A component is never empty");
+            let value = cells
+                .iter()
+                .try_fold(0u32, |value, &&pos| {
+                    let digit = schematic[pos]
+                        .to_digit(10)
+                        .expect("Only digits are grouped");
+                    value.checked_mul(10)?.checked_add(digit)
+                })
+                .ok_or_else(|| format!("The number at ({start_x}, {y}) is too large"))?;
+            Ok(Number {
+                y,
+                start_x,
+                end_x,
+                value,
+            })
+        })
+        .collect()
 }
 
-fn parse_line(input: &str) -> IResult<&str, Vec<ParseToken>> {
-    many1(map(
-        alt((
-            map(many1_count(character::complete::char('.')), |size| {
-                (size, TokenType::Empty)
-            }),
-            map(digit1, |digits: &str| {
-                (
-                    digits.len(),
-                    TokenType::Number(digits.parse::<usize>().expect("Should be digits")),
-                )
-            }),
-            map(anychar, |c| (1, TokenType::Symbol(c))),
-        )),
-        |(size, token_type)| ParseToken { token_type, size },
-    ))(input)
+// An empty input is an empty schematic
+fn parse_schematic(input: &str) -> Result<(Grid<char>, Vec<Number>), String> {
+    if input.trim().is_empty() {
+        return Ok((Grid::new(0, 0, '.'), Vec::new()));
+    }
+    let schematic = finish(input, char_grid).map_err(|err| format!("{err}"))?;
+    let numbers = find_numbers(&schematic)?;
+    Ok((schematic, numbers))
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    let mut numbers = Vec::new();
-    let mut symbol_positions = BTreeSet::new();
-    input.lines().enumerate().for_each(|(y, line)| {
-        let mut x = 0;
-        parse_line(line)
-            .into_iter()
-            .flat_map(|res| Some(res.1))
-            .for_each(|tokens| {
-                tokens.iter().for_each(|ParseToken { token_type, size }| {
-                    match token_type {
-                        TokenType::Number(value) => numbers.push(Number {
-                            y,
-                            start_x: x,
-                            end_x: x + size - 1,
-                            value: *value as u32,
-                        }),
-                        TokenType::Symbol(c) => {
-                            symbol_positions.insert(Pos::new(x as i32, y as i32));
-                        }
-                        TokenType::Empty => (),
-                    }
-                    x += size;
-                })
-            })
-    });
+pub fn solve_part1(input: &str) -> String {
+    let (schematic, numbers) = match parse_schematic(input) {
+        Ok(parsed) => parsed,
+        Err(err) => return err,
+    };
 
     numbers
         .iter()
         .filter(|&number| {
             number
-                .neighbours()
-                .iter()
-                .any(|neigh| symbol_positions.contains(neigh))
+                .neighbours(&schematic)
+                .any(|neigh| is_symbol(schematic[neigh]))
         })
-        .map(|num| num.value)
-        .sum()
+        .map(|num| num.value as u64)
+        .sum::<u64>()
+        .to_string()
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-    let mut cogs = Vec::new();
-    let mut number_positions = BTreeMap::new();
-    input.lines().enumerate().for_each(|(y, line)| {
-        let mut x = 0;
-        parse_line(line)
-            .into_iter()
-            .flat_map(|res| Some(res.1))
-            .for_each(|tokens| {
-                tokens.iter().for_each(|ParseToken { token_type, size }| {
-                    match token_type {
-                        TokenType::Number(value) => {
-                            let number = Number {
-                                y,
-                                start_x: x,
-                                end_x: x + size - 1,
-                                value: *value as u32,
-                            };
-                            number_positions.extend(
-                                (number.start_x..=number.end_x)
-                                    .map(|x| (Pos::new(x as i32, y as i32), number)),
-                            );
-                        }
-                        TokenType::Symbol('*') => {
-                            cogs.push(Symbol {
-                                y: y as i32,
-                                x: x as i32,
-                                value: '*',
-                            });
-                        }
-                        _ => (),
-                    }
-                    x += size;
-                })
-            })
+pub fn solve_part2(input: &str) -> String {
+    let (schematic, numbers) = match parse_schematic(input) {
+        Ok(parsed) => parsed,
+        Err(err) => return err,
+    };
+
+    let mut number_positions = Grid::new(schematic.width(), schematic.height(), None);
+    numbers.into_iter().for_each(|number| {
+        (number.start_x..=number.end_x).for_each(|x| number_positions[(x, number.y)] = Some(number))
     });
 
-    cogs.iter()
-        .filter_map(|cog| {
-            let mut adjecent_numbers = cog
-                .neighbours()
-                .into_iter()
-                .filter_map(|n| number_positions.get(&n))
+    schematic
+        .iter()
+        .filter(|(_, &c)| c == '*')
+        .filter_map(|((x, y), _)| {
+            let mut adjecent_numbers = schematic
+                .neighbours8(x, y)
+                .filter_map(|n| number_positions[n])
                 .unique();
 
             let (first, last) = (adjecent_numbers.next(), adjecent_numbers.next());
 
-            let product = first.and_then(|f| last.map(|l| f.value * l.value));
+            let product = first.and_then(|f| last.map(|l| f.value as u128 * l.value as u128));
 
             // Make sure the cog desnt have >2 neighbours. Undefined!
             product.filter(|_| adjecent_numbers.next().is_none())
         })
-        .sum()
+        .sum::<u128>()
+        .to_string()
 }
 
 #[cfg(test)]
@@ -201,38 +130,16 @@ mod test_day3 {
     use super::*;
 
     #[test]
-    fn test_parse_line() {
-        use TokenType::*;
-        let input = "....35x...67";
-
+    fn test_find_numbers() {
+        let schematic = Grid::parse("467..1\n..*.35", |c| c).unwrap();
         assert_eq!(
-            parse_line(input),
-            Ok((
-                "",
-                vec![
-                    ParseToken {
-                        token_type: Empty,
-                        size: 4
-                    },
-                    ParseToken {
-                        token_type: Number(35),
-                        size: 2
-                    },
-                    ParseToken {
-                        token_type: Symbol('x'),
-                        size: 1
-                    },
-                    ParseToken {
-                        token_type: Empty,
-                        size: 3
-                    },
-                    ParseToken {
-                        token_type: Number(67),
-                        size: 2
-                    }
-                ]
-            ))
-        )
+            vec![(467, 0, 0, 2), (1, 0, 5, 5), (35, 1, 4, 5)],
+            find_numbers(&schematic)
+                .unwrap()
+                .iter()
+                .map(|n| (n.value, n.y, n.start_x, n.end_x))
+                .collect::<Vec<_>>()
+        );
     }
 
     #[test]
@@ -247,7 +154,7 @@ mod test_day3 {
 ......755.
 ...$.*....
 .664.598.."#;
-        let expected = 4361;
+        let expected = "4361";
         assert_eq!(expected, solve_part1(input))
     }
     #[test]
@@ -262,7 +169,18 @@ mod test_day3 {
 ......755.
 ...$.*....
 .664.598.."#;
-        let expected = 467835;
+        let expected = "467835";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_schematic() {
+        let error = solve_part1("467..\n..*\n");
+        assert!(error.starts_with("line 2, column 4"), "{error}");
+        assert_eq!(
+            "The number at (1, 0) is too large",
+            solve_part2(".99999999999\n*...........")
+        );
+        assert_eq!("0", solve_part2("\n"));
+    }
 }
@@ -4,12 +4,13 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{space0, space1, u32},
-    multi::separated_list1,
-    sequence::tuple,
-    IResult,
+    character::complete::{char, space1, u32},
+    sequence::{pair, preceded, separated_pair},
+    IResult, Parser,
 };
 
+use crate::utils::parse::{finish, labelled, lines, list, ParseError};
+
 #[derive(Debug, PartialEq)]
 struct Card {
     id: u32,
@@ -18,51 +19,73 @@ struct Card {
 }
 
 fn parse_card(input: &str) -> IResult<&str, Card> {
-    let (input, (_, _, id, _, _)) =
-        tuple((tag("Card"), space1, u32, tag(":"), space1))(input)?;
-    let (input, winning) = separated_list1(space1, u32)(input)?;
-    let (input, _) = tuple((space0, tag("|"), space0))(input)?;
-    let (input, numbers) = separated_list1(space1, u32)(input)?;
-
-    Ok((
-        input,
-        Card {
-            id,
-            winning: winning.into_iter().collect(),
-            numbers: numbers.into_iter().collect(),
-        },
-    ))
+    labelled(
+        preceded(pair(tag("Card"), space1), u32),
+        separated_pair(list(u32), char('|'), list(u32)),
+    )
+    .map(|(id, (winning, numbers))| Card {
+        id,
+        winning: winning.into_iter().collect(),
+        numbers: numbers.into_iter().collect(),
+    })
+    .parse(input)
+}
+
+// An empty input is no cards
+fn parse_cards(input: &str) -> Result<Vec<Card>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(parse_card))
 }
 
-pub fn solve_part1(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| parse_card(line).expect("Prefer crash if parse fails."))
-        .map(|(_, card)| card.winning.intersection(&card.numbers).count() as u32)
+// Points double with every match, so a card with many matches can outgrow any integer
+pub fn solve_part1(input: &str) -> String {
+    let cards = match parse_cards(input) {
+        Ok(cards) => cards,
+        Err(err) => return format!("{err}"),
+    };
+
+    cards
+        .iter()
+        .map(|card| card.winning.intersection(&card.numbers).count() as u32)
         .filter(|&nr_of_winning| nr_of_winning > 0)
-        .map(|nr_of_winning| 2_u32.pow(nr_of_winning - 1))
-        .sum()
+        .try_fold(0_u128, |sum, nr_of_winning| {
+            sum.checked_add(1_u128.checked_shl(nr_of_winning - 1)?)
+        })
+        .map_or("The points overflow a u128".to_string(), |sum| {
+            sum.to_string()
+        })
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-    input
-        .lines()
-        .map(|line| parse_card(line).expect("Prefer crash if parse fails.").1)
-        .fold(BTreeMap::<u32, u32>::new(), |mut map, card| {
+pub fn solve_part2(input: &str) -> String {
+    let cards = match parse_cards(input) {
+        Ok(cards) => cards,
+        Err(err) => return format!("{err}"),
+    };
+
+    cards
+        .iter()
+        .try_fold(BTreeMap::<u64, u128>::new(), |mut map, card| {
             let matches = card.winning.intersection(&card.numbers).count();
+            let id = card.id as u64;
 
-            let copies_of_current = map.entry(card.id).or_insert(1).clone();
+            let copies_of_current = *map.entry(id).or_insert(1);
 
-            for offset in (1..=matches as u32) {
-                map.entry(card.id + offset)
-                    .and_modify(|e| *e += copies_of_current)
-                    .or_insert(1 + copies_of_current); // original + copies
+            for offset in (1..=matches as u64) {
+                let copies = map.entry(id + offset).or_insert(1); // original
+                *copies = copies.checked_add(copies_of_current)?;
             }
 
-            map
+            Some(map)
+        })
+        .and_then(|map| {
+            map.values()
+                .try_fold(0_u128, |sum, &copies| sum.checked_add(copies))
+        })
+        .map_or("The number of cards overflows a u128".to_string(), |sum| {
+            sum.to_string()
         })
-        .values()
-        .sum()
 }
 
 #[cfg(test)]
@@ -95,7 +118,7 @@ Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-        assert_eq!(solve_part1(input), 13)
+        assert_eq!(solve_part1(input), "13")
     }
     #[test]
     fn test_part2() {
@@ -105,7 +128,24 @@ Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-        let expected = 30;
+        let expected = "30";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_cards() {
+        let input = "Card 1: 41 48 | 83 86\nCard 2: 13 32 | x";
+        let error = parse_cards(input).unwrap_err();
+        assert_eq!((2, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1(input));
+        assert_eq!("0", solve_part2(""));
+
+        let numbers = (1..=200)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input = format!("Card 1: {numbers} | {numbers}");
+        assert_eq!("The points overflow a u128", solve_part1(&input));
+        assert_eq!("401", solve_part2(&input));
+    }
 }
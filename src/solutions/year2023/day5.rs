@@ -3,11 +3,11 @@
 use indicatif::ParallelProgressIterator;
 use itertools::Itertools;
 use nom::{
-    bytes::complete::{tag, take_while},
-    character::complete::{i64, newline, space1},
+    bytes::complete::tag,
+    character::complete::{line_ending, not_line_ending, space1, u32},
     combinator::map,
     multi::{many1, separated_list1},
-    sequence::{preceded, separated_pair, tuple},
+    sequence::{pair, preceded, separated_pair, tuple},
     IResult, Parser,
 };
 use rayon::{
@@ -16,165 +16,159 @@ use rayon::{
 };
 use std::ops::Range;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct Mapping {
-    source_range: Range<i64>,
-    offset: i64,
-}
+use crate::utils::{
+    intervals::{IntervalSet, RangeMap},
+    parse::{blank_line, blocks, finish, lines, ParseError},
+};
 
-impl Mapping {
-    // Applies the mapping to a section of a range that overlaps
-    fn partial_apply(&self, second: &Range<i64>) -> Option<Range<i64>> {
-        let first = self.source_range.clone();
-        let max_start = first.start.max(second.start);
-        let min_end = first.end.min(second.end);
+type Mappings = Vec<RangeMap>;
+type Items = Vec<i64>;
 
-        (max_start < min_end).then(|| (max_start + self.offset)..(min_end + self.offset))
-    }
+// The almanac only has numbers that fit in a u32, which keeps every range end and offset
+// far from overflowing an i64
+fn number(input: &str) -> IResult<&str, i64> {
+    map(u32, i64::from)(input)
 }
 
-type Mappings = Vec<Vec<Mapping>>;
-type Items = Vec<i64>;
-
 fn parse_seeds(input: &str) -> IResult<&str, Items> {
-    preceded(tag("seeds: "), separated_list1(space1, i64))(input)
+    preceded(tag("seeds: "), separated_list1(space1, number))(input)
 }
 
-fn parse_seed_ranges(input: &str) -> IResult<&str, Vec<Range<i64>>> {
+fn parse_seed_ranges(input: &str) -> IResult<&str, IntervalSet> {
     preceded(
         tag("seeds:"),
         many1(preceded(
             space1,
-            map(separated_pair(i64, space1, i64), |(start, len)| {
+            map(separated_pair(number, space1, number), |(start, len)| {
                 start..(start + len)
             }),
         )),
-    )(input)
+    )
+    .map(IntervalSet::from_iter)
+    .parse(input)
 }
 
 fn parse_mappings(input: &str) -> IResult<&str, Mappings> {
-    fn skip_to_next_mappings(input: &str) -> IResult<&str, &str> {
-        take_while(|ch: char| !ch.is_digit(10))(input)
-    }
-    fn mapping(input: &str) -> IResult<&str, Mapping> {
-        tuple((i64, space1, i64, space1, i64))
-            .map(|(dest, _, source, _, length)| Mapping {
-                source_range: source..(source + length),
-                offset: dest - source,
-            })
+    fn mapping(input: &str) -> IResult<&str, (Range<i64>, i64)> {
+        tuple((number, space1, number, space1, number))
+            .map(|(dest, _, source, _, length)| (source..(source + length), dest - source))
             .parse(input)
     }
-    many1(preceded(
-        skip_to_next_mappings,
-        separated_list1(newline, mapping),
-    ))(input)
+    // Each block starts with a `seed-to-soil map:` style header
+    fn layer(input: &str) -> IResult<&str, RangeMap> {
+        preceded(
+            pair(not_line_ending, line_ending),
+            lines(mapping).map(RangeMap::from_iter),
+        )(input)
+    }
+    preceded(blank_line, blocks(layer))(input)
 }
 
-fn apply_mapping_layer(item: i64, map_layer: &Vec<Mapping>) -> i64 {
-    map_layer
-        .iter()
-        .find(|m| m.source_range.contains(&item))
-        .and_then(|m| Some(item + m.offset))
-        .unwrap_or(item)
+// The seeds line read by `seeds`, then the mappings
+fn parse_almanac<'a, S>(
+    input: &'a str,
+    seeds: impl Parser<&'a str, S, nom::error::Error<&'a str>>,
+) -> Result<(S, Mappings), ParseError> {
+    finish(input, pair(seeds, parse_mappings))
 }
 
 pub fn solve_part1(input: &str) -> String {
-    let (input, seeds) = parse_seeds(input).expect("Parser should work");
-    let (_, mappings) = parse_mappings(input).unwrap();
+    let (seeds, mappings) = match parse_almanac(input, parse_seeds) {
+        Ok(almanac) => almanac,
+        Err(err) => return format!("{err}"),
+    };
 
     let locations = mappings.iter().fold(seeds, |items, mapping| {
-        let new_prods = items
-            .iter()
-            .map(|&item| apply_mapping_layer(item, mapping))
-            .collect();
+        let new_prods = items.iter().map(|&item| mapping.apply(item)).collect();
 
         new_prods
     });
 
-    locations.iter().min().unwrap().to_string()
+    locations
+        .iter()
+        .min()
+        .expect("There is at least one seed")
+        .to_string()
 }
 
 pub fn solve_part2_brute(input: &str) -> String {
-    let (input, seed_ranges) = parse_seed_ranges(input).expect("parser should work");
-    let (_, mappings) = parse_mappings(input).unwrap();
+    let (seed_ranges, mappings) = match parse_almanac(input, parse_seed_ranges) {
+        Ok(almanac) => almanac,
+        Err(err) => return format!("{err}"),
+    };
 
-    let number_of_seed_ranges = seed_ranges.len() as u64;
+    let number_of_seed_ranges = seed_ranges.ranges().len() as u64;
 
     // Brute force
     let min_location = seed_ranges
+        .ranges()
+        .to_vec()
         .into_par_iter()
         .flat_map(|sr| {
-            sr.map(|seed| mappings.iter().fold(seed, apply_mapping_layer))
+            sr.map(|seed| mappings.iter().fold(seed, |item, layer| layer.apply(item)))
                 .min()
         })
         .progress_count(number_of_seed_ranges)
         .min();
 
-    min_location.unwrap().to_string()
-}
-
-fn fill_gaps(mappings: Vec<Mapping>) -> Vec<Mapping> {
-    let (mut result, end) =
-        mappings
-            .into_iter()
-            .fold((Vec::new(), 0), |(mut result, last_end), range| {
-                if range.source_range.start > last_end {
-                    // Unit mapping
-                    result.push(Mapping {
-                        source_range: last_end..range.source_range.start,
-                        offset: 0,
-                    });
-                }
-                // The real mapping
-                result.push(range.clone());
-
-                let new_end = last_end.max(range.source_range.end);
-                (result, new_end)
-            });
-
-    result.push(Mapping {
-        source_range: end..i64::max_value(),
-        offset: 0,
-    });
-
-    result
+    match min_location {
+        Some(location) => location.to_string(),
+        None => "Every seed range is empty".to_string(),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let (input, seed_ranges) = parse_seed_ranges(input).unwrap();
-    let (_, mapping_layers) = parse_mappings(input).unwrap();
-
-    let filled_mapping_layers = mapping_layers
-        .into_iter()
-        .map(|mut layer| {
-            layer.sort_by_key(|mapping| mapping.source_range.start);
-            layer
-        })
-        .map(fill_gaps);
-
-    let location_ranges = filled_mapping_layers.fold(seed_ranges, |item_ranges, layer| {
-        item_ranges
-            .iter()
-            .flat_map(|item_range| {
-                layer
-                    .iter()
-                    .filter_map(|mapping| mapping.partial_apply(item_range))
-            })
-            .collect_vec()
-    });
+    let (seed_ranges, mapping_layers) = match parse_almanac(input, parse_seed_ranges) {
+        Ok(almanac) => almanac,
+        Err(err) => return format!("{err}"),
+    };
 
-    let min_location = location_ranges
-        .into_iter()
-        .flat_map(|range| range.min())
-        .min()
-        .expect("There has to be a minimum");
+    let locations = mapping_layers
+        .iter()
+        .fold(seed_ranges, |items, layer| layer.apply_set(&items));
 
-    min_location.to_string()
+    match locations.min() {
+        Some(location) => location.to_string(),
+        None => "Every seed range is empty".to_string(),
+    }
 }
 
 #[cfg(test)]
 mod test_day5 {
     use super::*;
+    use crate::utils::prop::check;
+
+    #[test]
+    fn prop_apply_set_matches_apply() {
+        // Mappings as (gap before, length, offset), followed by the seed range start and length
+        check(
+            "apply_set on a layer maps the same seeds as apply",
+            |rng| {
+                let mappings = (0..rng.index(6))
+                    .map(|_| (rng.range(0..10), rng.range(1..10), rng.range(-20..20)))
+                    .collect_vec();
+                (mappings, rng.range(0..80), rng.range(1..30))
+            },
+            |(mappings, start, len)| {
+                let mut end_of_last = 0;
+                let layer = mappings
+                    .iter()
+                    .map(|&(gap, length, offset)| {
+                        let source_start = end_of_last + gap;
+                        end_of_last = source_start + length;
+                        (source_start..end_of_last, offset)
+                    })
+                    .collect::<RangeMap>();
+                let seeds = *start..(start + len);
+
+                let mapped = layer.apply_set(&IntervalSet::from_iter([seeds.clone()]));
+                let expected = seeds
+                    .map(|seed| layer.apply(seed)..layer.apply(seed) + 1)
+                    .collect::<IntervalSet>();
+                mapped == expected
+            },
+        )
+    }
 
     #[test]
     fn test_part1() {
@@ -252,4 +246,18 @@ humidity-to-location map:
         let expected = "46";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_almanac() {
+        let input = "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 x 48\n";
+        let error = parse_almanac(input, parse_seeds).unwrap_err();
+        assert_eq!((5, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2(input));
+        assert!(parse_almanac("seeds: 79 -14\n\nmap:\n1 2 3", parse_seeds).is_err());
+        assert!(parse_almanac("seeds: 79 14", parse_seeds).is_err());
+
+        let input = "seeds: 79 0\n\nseed-to-soil map:\n50 98 2";
+        assert_eq!("Every seed range is empty", solve_part2(input));
+        assert_eq!("0", solve_part1(input));
+    }
 }
@@ -3,20 +3,22 @@
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{digit1, newline, space1, u64},
-    combinator::map,
+    character::complete::{digit1, line_ending, space1, u64},
+    combinator::map_res,
     multi::many1,
     sequence::{preceded, separated_pair},
-    IResult,
+    IResult, Parser,
 };
 
+use crate::utils::parse::{finish, labelled, list, ParseError};
+
 type Time = u64;
 type Record = u64;
 fn parse_race_details(input: &str) -> IResult<&str, (Vec<Time>, Vec<Record>)> {
     separated_pair(
-        preceded(tag("Time:"), many1(preceded(space1, u64))),
-        newline,
-        preceded(tag("Distance:"), many1(preceded(space1, u64))),
+        labelled(tag("Time"), list(u64)).map(|(_, times)| times),
+        line_ending,
+        labelled(tag("Distance"), list(u64)).map(|(_, records)| records),
     )(input)
 }
 
@@ -24,53 +26,65 @@ fn parse_race_details_kerning(input: &str) -> IResult<&str, (Time, Record)> {
     separated_pair(
         preceded(
             tag("Time:"),
-            map(many1(preceded(space1, digit1)), |numbers| {
-                numbers.join("").parse().unwrap()
+            map_res(many1(preceded(space1, digit1)), |numbers| {
+                numbers.join("").parse()
             }),
         ),
-        newline,
+        line_ending,
         preceded(
             tag("Distance:"),
-            map(many1(preceded(space1, digit1)), |numbers| {
-                numbers.join("").parse().unwrap()
+            map_res(many1(preceded(space1, digit1)), |numbers| {
+                numbers.join("").parse()
             }),
         ),
     )(input)
 }
 
+// The distance h * (time - h) grows until half the time, so the shortest winning hold
+// is found by bisecting there and the longest one mirrors it. Races far too long to try
+// every hold time are fine.
+fn winning_strategies(time: Time, record: Record) -> u64 {
+    let (time, record) = (time as u128, record as u128);
+    let distance = |hold_time: u128| hold_time * (time - hold_time);
+    let (mut low, mut high) = (0, time / 2);
+    if distance(high) <= record {
+        return 0;
+    }
+    while low < high {
+        let mid = (low + high) / 2;
+        if distance(mid) > record {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    (time - 2 * low + 1) as u64
+}
+
 pub fn solve_part1(input: &str) -> String {
-    let (_, (times, records)) = parse_race_details(input).unwrap();
+    let (times, records) = match finish(input, parse_race_details) {
+        Ok(races) => races,
+        Err(err) => return format!("{err}"),
+    };
+    if times.len() != records.len() {
+        return format!("{} times but {} distances", times.len(), records.len());
+    }
 
     let race_entries = times.into_iter().zip(records);
 
-    let winning_strategies_per_race = race_entries
-        .map(|(time, record)| {
-            (0..=time)
-                .map(|hold_time| {
-                    let remaining_time = time - hold_time;
-                    hold_time * remaining_time
-                })
-                .filter(|distance_traveled| distance_traveled > &record)
-                .count()
+    race_entries
+        .map(|(time, record)| winning_strategies(time, record) as u128)
+        .try_fold(1_u128, |product, ways| product.checked_mul(ways))
+        .map_or("The product overflows a u128".to_string(), |product| {
+            product.to_string()
         })
-        .collect_vec();
-
-    winning_strategies_per_race
-        .iter()
-        .product::<usize>()
-        .to_string()
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let (_, (time, record)) = parse_race_details_kerning(input).unwrap();
-
-    (0..=time)
-        .map(|hold_time| {
-            let remaining_time = time - hold_time;
-            hold_time * remaining_time
-        })
-        .filter(|distance_traveled| distance_traveled > &record)
-        .count().to_string()
+    match finish(input, parse_race_details_kerning) {
+        Ok((time, record)) => winning_strategies(time, record).to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 #[cfg(test)]
@@ -89,4 +103,28 @@ mod test_day6 {
         let expected = "71503";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_winning_strategies() {
+        assert_eq!(4, winning_strategies(7, 9));
+        assert_eq!(9, winning_strategies(30, 200));
+        assert_eq!(0, winning_strategies(4, 4));
+        assert_eq!(1, winning_strategies(2, 0));
+        assert_eq!(0, winning_strategies(0, 0));
+        // Every hold but none and all of it
+        assert_eq!(u64::MAX - 1, winning_strategies(u64::MAX, 0));
+    }
+
+    #[test]
+    fn test_bad_races() {
+        let error = finish("Time: 7 15\nDistance: 9 x", parse_race_details).unwrap_err();
+        assert_eq!((2, 13), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1("Time: 7 15\nDistance: 9 x"));
+        assert_eq!(
+            "2 times but 1 distances",
+            solve_part1("Time: 7 15\nDistance: 9")
+        );
+        let error = solve_part2("Time: 99999999999 99999999999\nDistance: 9");
+        assert!(error.starts_with("line 1, column 6"), "{error}");
+    }
 }
@@ -4,13 +4,15 @@ use std::collections::{btree_map, BTreeMap};
 
 use itertools::Itertools;
 use nom::{
-    character::complete::{alphanumeric1, newline, space1, u32},
-    combinator::map,
-    multi::separated_list1,
+    character::complete::{one_of, space1, u32},
+    combinator::{map, recognize},
+    multi::count,
     sequence::separated_pair,
     IResult,
 };
 
+use crate::utils::parse::{finish, lines, ParseError};
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
 enum Kind {
     FiveOfAKind,
@@ -81,24 +83,31 @@ impl Hand<'_> {
     }
 }
 
-fn parse_hands(input: &str) -> IResult<&str, Vec<Hand>> {
+// Every hand is five cards, an empty input is no hands
+fn parse_hands(input: &str) -> Result<Vec<Hand<'_>>, ParseError> {
     fn parse_hand(input: &str) -> IResult<&str, Hand> {
         map(
-            separated_pair(alphanumeric1, space1, u32),
+            separated_pair(recognize(count(one_of("23456789TJQKA"), 5)), space1, u32),
             |(cards, bid)| Hand { cards, bid },
         )(input)
     }
 
-    separated_list1(newline, parse_hand)(input)
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(parse_hand))
 }
 
 pub fn solve_part1(input: &str) -> String {
     let value_mapping = BTreeMap::from(
         [('T', 10), ('J', 11), ('Q', 12), ('K', 13), ('A', 14)]);
 
-    parse_hands(input)
-        .expect("parsing works")
-        .1
+    let hands = match parse_hands(input) {
+        Ok(hands) => hands,
+        Err(err) => return format!("{err}"),
+    };
+
+    hands
         .iter()
         .sorted_by(|a, b| {
             b.kind().cmp(&a.kind()).then(
@@ -107,8 +116,8 @@ pub fn solve_part1(input: &str) -> String {
             )
         })
         .enumerate()
-        .map(|(rank, hand)| (rank + 1) * hand.bid as usize)
-        .sum::<usize>()
+        .map(|(rank, hand)| (rank as u64 + 1) * hand.bid as u64)
+        .sum::<u64>()
         .to_string()
 }
 
@@ -116,9 +125,12 @@ pub fn solve_part2(input: &str) -> String {
     let value_mapping = BTreeMap::from(
         [('T', 10), ('J', 1), ('Q', 12), ('K', 13), ('A', 14)]);
 
-    parse_hands(input)
-        .expect("parsing works")
-        .1
+    let hands = match parse_hands(input) {
+        Ok(hands) => hands,
+        Err(err) => return format!("{err}"),
+    };
+
+    hands
         .into_iter()
         .sorted_by(|a, b| {
             b.joker_kind().cmp(&a.joker_kind()).then(
@@ -127,8 +139,8 @@ pub fn solve_part2(input: &str) -> String {
             )
         })
         .enumerate()
-        .map(|(rank, hand)| (rank + 1) * hand.bid as usize)
-        .sum::<usize>()
+        .map(|(rank, hand)| (rank as u64 + 1) * hand.bid as u64)
+        .sum::<u64>()
         .to_string()
 }
 
@@ -148,4 +160,13 @@ mod test_day7 {
         let expected = "5905";
         assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_hands() {
+        let error = parse_hands("32T3K 765\nT55J 684").unwrap_err();
+        assert_eq!((2, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2("32T3K 765\nT55J 684"));
+        assert!(parse_hands("32t3K 765").is_err());
+        assert_eq!("0", solve_part1(""));
+    }
 }
@@ -1,19 +1,25 @@
 #![allow(unused)]
 
-use std::{collections::{HashMap, HashSet}};
+use std::collections::HashSet;
 
 use itertools::Itertools;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till},
-    character::complete::{self, alpha1, newline},
+    bytes::complete::tag,
+    character::complete::{self, alpha1},
     combinator::map,
-    multi::{many1, separated_list1},
+    multi::many1,
     sequence::{delimited, separated_pair},
     IResult,
 };
 use num::Integer;
 
+use crate::utils::{
+    graph::Graph,
+    number_theory::lcm_all,
+    parse::{blank_line, finish, lines, ParseError},
+};
+
 #[derive(Debug)]
 struct Node<'a> {
     key: &'a str,
@@ -21,7 +27,7 @@ struct Node<'a> {
     right: &'a str,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Instruction {
     Left,
     Right,
@@ -34,7 +40,8 @@ fn instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
     )))(input)
 }
 
-fn nodes(input: &str) -> IResult<&str, HashMap<&str, Node>> {
+// Every node has an edge for going left and one for going right
+fn nodes(input: &str) -> IResult<&str, Graph<&str, Instruction>> {
     fn node(input: &str) -> IResult<&str, Node> {
         let (input, (key, (left, right))) = separated_pair(
             alpha1,
@@ -48,106 +55,97 @@ fn nodes(input: &str) -> IResult<&str, HashMap<&str, Node>> {
 
         Ok((input, Node { key, left, right }))
     }
-    let (input, nodes) = separated_list1(newline, node)(input)?;
-    let graph = nodes
-        .into_iter()
-        .map(|node| (node.key, node))
-        .collect::<HashMap<&str, Node>>();
+    let (input, nodes) = lines(node)(input)?;
+    let mut graph = Graph::new();
+    for node in nodes {
+        graph.add_edge(node.key, node.left, Instruction::Left);
+        graph.add_edge(node.key, node.right, Instruction::Right);
+    }
 
     Ok((input, graph))
 }
 
-fn parse_input(input: &str) -> (Vec<Instruction>, HashMap<&str, Node>) {
-    let (_, (instructions, graph)) =
-        separated_pair(instructions, take_till(|c: char| c.is_alphabetic()), nodes)(input)
-            .expect("Parser works");
-
-    (instructions, graph)
+fn parse_input(input: &str) -> Result<(Vec<Instruction>, Graph<&str, Instruction>), ParseError> {
+    finish(input, separated_pair(instructions, blank_line, nodes))
 }
 
-pub fn solve_part1(input: &str) -> String {
-    let (instructions, graph) = parse_input(input);
-
-    let iter_count = instructions
-        .into_iter()
+// The nodes visited from `start`, stopping before the first node that has no edge for
+// the instruction
+fn walk<'a>(
+    graph: &'a Graph<&str, Instruction>,
+    instructions: &'a [Instruction],
+    start: usize,
+) -> impl Iterator<Item = usize> + 'a {
+    instructions
+        .iter()
         .cycle()
-        .scan("AAA", |mut key, instruction| {
-            let opt_node = graph.get(key);
-
-            if let Some(node) = opt_node {
-                match instruction {
-                    Instruction::Left => *key = node.left,
-                    Instruction::Right => *key = node.right,
-                }
-            }
-            opt_node
+        .scan(start, |node, instruction| {
+            let current = *node;
+            *node = graph.follow(current, instruction)?;
+            Some(current)
         })
-        .take_while(|node| node.key != "ZZZ")
-        .count();
-
-    iter_count.to_string()
 }
 
-pub fn lcm(nums: &[u128]) -> u128 {
-    if nums.len() == 1 {
-        return nums[0];
-    }
-    let a = nums[0];
-    let b = lcm(&nums[1..]);
-    a * b / gcd_of_two_numbers(a, b)
+// A walk that hasn't reached an end after this many steps has been in some node at the
+// same point of the instructions twice, so it goes round in circles from there on
+fn step_limit(graph: &Graph<&str, Instruction>, instructions: &[Instruction]) -> usize {
+    graph.len().saturating_mul(instructions.len())
 }
 
-fn gcd_of_two_numbers(a: u128, b: u128) -> u128 {
-    if b == 0 {
-        return a;
+pub fn solve_part1(input: &str) -> String {
+    let (instructions, graph) = match parse_input(input) {
+        Ok(parsed) => parsed,
+        Err(err) => return format!("{err}"),
+    };
+    let (Some(start), end) = (graph.id("AAA"), graph.id("ZZZ")) else {
+        return 0.to_string();
+    };
+
+    let iter_count = walk(&graph, &instructions, start)
+        .take(step_limit(&graph, &instructions).saturating_add(1))
+        .position(|node| Some(node) == end);
+
+    match iter_count {
+        Some(iter_count) => iter_count.to_string(),
+        None => "ZZZ can't be reached from AAA".to_string(),
     }
-    gcd_of_two_numbers(b, a % b)
 }
 
 pub fn solve_part2(input: &str) -> String {
-    let (instructions, graph) = parse_input(input);
+    let (instructions, graph) = match parse_input(input) {
+        Ok(parsed) => parsed,
+        Err(err) => return format!("{err}"),
+    };
 
     let starts = graph
-        .keys()
-        .filter_map(|key| key.ends_with("A").then_some(*key))
-        .collect::<HashSet<&str>>();
-
-    dbg!(&starts);
+        .nodes()
+        .filter(|&(id, key)| key.ends_with('A') && !graph.edges(id).is_empty())
+        .map(|(id, _)| id)
+        .collect::<HashSet<usize>>();
 
     let ends = graph
-        .keys()
-        .filter_map(|key| key.ends_with("Z").then_some(*key))
-        .collect::<HashSet<&str>>();
+        .nodes()
+        .filter_map(|(id, key)| key.ends_with('Z').then_some(id))
+        .collect::<HashSet<usize>>();
 
-    dbg!(&ends);
     let loop_length = starts
         .iter()
-        .map(|start| {
-            instructions
-                .iter()
-                .cycle()
-                .scan(start, |mut key, instruction| {
-                    let opt_node = graph.get(*key);
-
-                    if let Some(node) = opt_node {
-                        match instruction {
-                            Instruction::Left => *key = &node.left,
-                            Instruction::Right => *key = &node.right,
-                        }
-                    }
-                    opt_node
-                })
+        .map(|&start| {
+            walk(&graph, &instructions, start)
                 .skip(1)
-                .take_while(|node| !ends.contains(node.key))
-                .count() as u128
+                .take(step_limit(&graph, &instructions).saturating_add(1))
+                .position(|node| ends.contains(&node))
+                .map(|count| count as u64)
         })
-        .collect_vec();
-
-    dbg!(&loop_length);
-    
-    let result = lcm(&loop_length);
-    
-    result.to_string()
+        .collect::<Option<Vec<_>>>();
+
+    let Some(loop_length) = loop_length else {
+        return "A start never reaches a node ending in Z".to_string();
+    };
+    match lcm_all(&loop_length) {
+        Some(result) => result.to_string(),
+        None => "The lcm of the loop lengths overflows a u64".to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -195,4 +193,23 @@ XXX = (XXX, XXX)";
         let expected = "6";
         //assert_eq!(expected, solve_part2(input))
     }
+
+    #[test]
+    fn test_bad_network() {
+        let input = "RL\n\nAAA = (BBB, CCC)\nBBB = (DDD EEE)";
+        let error = parse_input(input).map(|_| ()).unwrap_err();
+        assert_eq!((4, 1), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part2(input));
+        assert!(parse_input("RXL\n\nAAA = (AAA, AAA)").is_err());
+
+        // Going round in circles, and stopping where there is no way left
+        let input = "L\n\nAAA = (BBB, ZZZ)\nBBB = (AAA, ZZZ)";
+        assert_eq!("ZZZ can't be reached from AAA", solve_part1(input));
+        assert_eq!(
+            "A start never reaches a node ending in Z",
+            solve_part2(input)
+        );
+        let input = "L\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)";
+        assert_eq!("ZZZ can't be reached from AAA", solve_part1(input));
+    }
 }
@@ -1,74 +1,99 @@
 #![allow(unused)]
 
 use itertools::Itertools;
+use num::BigInt;
 
-fn next_number(numbers: Vec<i64>) -> i64 {
-    if numbers.iter().all(|&i| i == 0) {
-        0
-    } else {
-        let last = numbers.last().expect("all() is true if list empty");
-        last + next_number(
-            numbers
-                .iter()
-                .tuple_windows()
-                .map(|(a, b)| b - a)
-                .collect_vec(),
-        )
-    }
+use crate::utils::{
+    parse::{finish, lines, signed_numbers, ParseError},
+    polynomial::Polynomial,
+};
+
+// The value `index` steps from the start of the history, negative indices go backwards.
+// A history that never reaches a row of zeros, like a single value, is taken to reach
+// it on the row after its last, which is what working the differences out by hand does.
+// Values far outside the history can outgrow an i64, so they stay big integers.
+fn extrapolate(numbers: &[i64], index: i64) -> BigInt {
+    Polynomial::fit(numbers)
+        .unwrap_or_else(|_| Polynomial::interpolate(numbers))
+        .at_index(index)
+        .to_integer()
 }
 
-fn prev_number(numbers: Vec<i64>) -> i64 {
-    let numbers = numbers.into_iter().rev().collect();
-    next_number(numbers)
+fn next_number(numbers: Vec<i64>) -> BigInt {
+    extrapolate(&numbers, numbers.len() as i64)
 }
 
-fn parse_num_vecs(input: &str) -> Vec<Vec<i64>> {
-    input
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|n| n.parse().expect("is number"))
-                .collect_vec()
-        })
-        .collect_vec()
+fn prev_number(numbers: Vec<i64>) -> BigInt {
+    extrapolate(&numbers, -1)
+}
+
+// An empty input is no histories, `lines` wants at least one
+fn parse_num_vecs(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    finish(input, lines(signed_numbers))
 }
 
 pub fn solve_part1(input: &str) -> String {
-    parse_num_vecs(input)
-        .into_iter()
-        .map(next_number)
-        .sum::<i64>()
-        .to_string()
+    match parse_num_vecs(input) {
+        Ok(histories) => histories
+            .into_iter()
+            .map(next_number)
+            .sum::<BigInt>()
+            .to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 pub fn solve_part2(input: &str) -> String {
-    parse_num_vecs(input)
-        .into_iter()
-        .map(prev_number)
-        .sum::<i64>()
-        .to_string()
+    match parse_num_vecs(input) {
+        Ok(histories) => histories
+            .into_iter()
+            .map(prev_number)
+            .sum::<BigInt>()
+            .to_string(),
+        Err(err) => format!("{err}"),
+    }
 }
 
 #[cfg(test)]
 mod test_day9 {
     use super::*;
+    use crate::utils::prop::check;
+
+    #[test]
+    fn prop_prev_of_reversed_is_next() {
+        check(
+            "prev_number of a reversed sequence equals next_number",
+            |rng| {
+                (0..rng.range(1..10))
+                    .map(|_| rng.range(-1000..1000))
+                    .collect::<Vec<i64>>()
+            },
+            |numbers| {
+                let reversed = numbers.iter().rev().copied().collect_vec();
+                prev_number(reversed) == next_number(numbers.clone())
+            },
+        )
+    }
 
     #[test]
     fn test_part1_a() {
         let input = vec![0, 3, 6, 9, 12, 15];
-        let expected = 18;
+        let expected = BigInt::from(18);
         assert_eq!(expected, next_number(input))
     }
     #[test]
     fn test_part1_b() {
         let input = vec![1, 3, 6, 10, 15, 21];
-        let expected = 28;
+        let expected = BigInt::from(28);
         assert_eq!(expected, next_number(input))
     }
     #[test]
     fn test_part1_c() {
         let input = vec![10, 13, 16, 21, 30, 45];
-        let expected = 68;
+        let expected = BigInt::from(68);
         assert_eq!(expected, next_number(input))
     }
     #[test]
@@ -79,6 +104,34 @@ mod test_day9 {
         let expected = "114";
         assert_eq!(expected, solve_part1(input))
     }
+    #[test]
+    fn test_not_polynomial() {
+        assert_eq!("5", solve_part1("5"));
+        assert_eq!("5", solve_part2("5"));
+        assert_eq!(BigInt::from(31), next_number(vec![1, 2, 4, 8, 16]));
+        assert_eq!(BigInt::from(1), prev_number(vec![1, 2, 4, 8, 16]));
+    }
+
+    #[test]
+    fn test_bad_histories() {
+        let error = parse_num_vecs("0 3 6\n1 ¡").unwrap_err();
+        assert_eq!((2, 3), (error.line, error.column));
+        assert_eq!(error.to_string(), solve_part1("0 3 6\n1 ¡"));
+
+        // Twice the largest i64
+        let input = format!("0 {}", i64::MAX);
+        assert_eq!(
+            (BigInt::from(i64::MAX) * 2_i64).to_string(),
+            solve_part1(&input)
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!("0", solve_part1(""));
+        assert_eq!("0", solve_part2("\n"));
+    }
+
     #[test]
     fn test_part2() {
         let input = "0 3 6 9 12 15
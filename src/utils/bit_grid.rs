@@ -0,0 +1,293 @@
+#![allow(unused)]
+
+// A grid of lights packed 64 to a word. Each row starts on a fresh word and the bits
+// past the width are kept at zero, so rows can be shifted, filled and counted a word at
+// a time.
+
+use std::{fmt, ops::Index};
+
+use super::grid::Grid;
+
+const BITS: usize = u64::BITS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    // Every light starts off
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        let words_per_row = width.div_ceil(BITS);
+        BitGrid {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn row_words(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    fn row_words_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    // Panics outside the grid, like indexing a Grid
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        assert!(x < self.width && y < self.height, "({x}, {y}) is outside");
+        self.row_words(y)[x / BITS] >> (x % BITS) & 1 == 1
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        assert!(x < self.width && y < self.height, "({x}, {y}) is outside");
+        let word = &mut self.row_words_mut(y)[x / BITS];
+        if on {
+            *word |= 1 << (x % BITS);
+        } else {
+            *word &= !(1 << (x % BITS));
+        }
+    }
+
+    // Sets every light in the rectangle with its top left corner at (x, y)
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, on: bool) {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "The rectangle doesn't fit"
+        );
+        let mask = span_mask(x, x + width, self.words_per_row);
+        for row in y..y + height {
+            for (word, mask) in self.row_words_mut(row).iter_mut().zip(&mask) {
+                if on {
+                    *word |= mask;
+                } else {
+                    *word &= !mask;
+                }
+            }
+        }
+    }
+
+    // Shifts a row to the right, lights falling off the end come back on the left
+    pub fn rotate_row(&mut self, y: usize, by: usize) {
+        let width = self.width;
+        rotate_bits(self.row_words_mut(y), width, by);
+    }
+
+    // Shifts a column down, lights falling off the bottom come back at the top. The column
+    // is gathered into words of its own and rotated like a row.
+    pub fn rotate_column(&mut self, x: usize, by: usize) {
+        let mut column = vec![0; self.height.div_ceil(BITS)];
+        for y in 0..self.height {
+            column[y / BITS] |= (self.get(x, y) as u64) << (y % BITS);
+        }
+        rotate_bits(&mut column, self.height, by);
+        for y in 0..self.height {
+            self.set(x, y, column[y / BITS] >> (y % BITS) & 1 == 1);
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    // One line per row
+    pub fn render(&self, on: char, off: char) -> String {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| if self.get(x, y) { on } else { off })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        Grid::from_fn(self.width, self.height, |x, y| self.get(x, y))
+    }
+}
+
+impl Index<(usize, usize)> for BitGrid {
+    type Output = bool;
+
+    fn index(&self, (x, y): (usize, usize)) -> &bool {
+        if self.get(x, y) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render('#', '.'))
+    }
+}
+
+// The bits from start up to end over a row of words
+fn span_mask(start: usize, end: usize, words: usize) -> Vec<u64> {
+    (0..words)
+        .map(|i| {
+            let (low, high) = (i * BITS, (i + 1) * BITS);
+            let (from, to) = (start.clamp(low, high) - low, end.clamp(low, high) - low);
+            match to - from {
+                0 => 0,
+                BITS => u64::MAX,
+                len => ((1 << len) - 1) << from,
+            }
+        })
+        .collect()
+}
+
+// Rotates the lowest `len` bits of the words towards the higher bits. The bits above
+// `len` have to be zero and stay zero.
+fn rotate_bits(words: &mut [u64], len: usize, by: usize) {
+    let by = by.checked_rem(len).unwrap_or(0);
+    if by == 0 {
+        return;
+    }
+    let up = shifted_up(words, by);
+    let down = shifted_down(words, len - by);
+    for ((word, up), down) in words.iter_mut().zip(up).zip(down) {
+        *word = up | down;
+    }
+    let tail = len % BITS;
+    if tail > 0 {
+        words[len / BITS] &= (1 << tail) - 1;
+    }
+}
+
+fn shifted_up(words: &[u64], by: usize) -> Vec<u64> {
+    let (skip, bits) = (by / BITS, by % BITS);
+    (0..words.len())
+        .map(|i| {
+            let Some(from) = i.checked_sub(skip) else {
+                return 0;
+            };
+            let carry = match from.checked_sub(1) {
+                Some(below) if bits > 0 => words[below] >> (BITS - bits),
+                _ => 0,
+            };
+            words[from] << bits | carry
+        })
+        .collect()
+}
+
+fn shifted_down(words: &[u64], by: usize) -> Vec<u64> {
+    let (skip, bits) = (by / BITS, by % BITS);
+    (0..words.len())
+        .map(|i| {
+            let Some(&word) = words.get(i + skip) else {
+                return 0;
+            };
+            let carry = match words.get(i + skip + 1) {
+                Some(above) if bits > 0 => above << (BITS - bits),
+                _ => 0,
+            };
+            word >> bits | carry
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_bit_grid {
+    use super::*;
+    use crate::utils::prop::check;
+
+    #[test]
+    fn test_fill_and_count() {
+        let mut lights = BitGrid::new(7, 3);
+        lights.fill_rect(0, 0, 3, 2, true);
+        assert_eq!(6, lights.count_ones());
+        lights.fill_rect(1, 1, 2, 1, false);
+        assert_eq!("###....\n#......\n.......", lights.to_string());
+        assert!(lights[(0, 1)]);
+        assert!(!lights[(1, 1)]);
+    }
+
+    #[test]
+    fn test_rotations_wrap() {
+        let mut lights = BitGrid::new(7, 3);
+        lights.fill_rect(0, 0, 3, 2, true);
+        lights.rotate_column(1, 1);
+        lights.rotate_row(0, 4);
+        lights.rotate_column(1, 1);
+        assert_eq!(".#..#.#\n#.#....\n.#.....", lights.to_string());
+        lights.rotate_row(2, 7 * 3 + 1);
+        assert_eq!(".#..#.#\n#.#....\n..#....", lights.to_string());
+    }
+
+    #[test]
+    fn test_wide_rows() {
+        // A span across a word boundary, rotated across the next one
+        let mut lights = BitGrid::new(150, 2);
+        lights.fill_rect(60, 1, 10, 1, true);
+        lights.rotate_row(1, 75);
+        assert_eq!(10, lights.count_ones());
+        assert!((135..145).all(|x| lights.get(x, 1)));
+        lights.rotate_row(1, 10);
+        assert!((145..150).chain(0..5).all(|x| lights.get(x, 1)));
+        assert_eq!(10, lights.count_ones());
+    }
+
+    #[test]
+    fn prop_matches_grid() {
+        check(
+            "rect and rotations match a plain Grid<bool>",
+            |rng| {
+                let size = (rng.range(1..140) as usize, rng.range(1..70) as usize);
+                let steps = (0..20)
+                    .map(|_| {
+                        let kind = rng.range(0..3) as usize;
+                        (kind, rng.range(0..140) as usize, rng.range(0..200) as usize)
+                    })
+                    .collect::<Vec<_>>();
+                (size, steps)
+            },
+            |&((width, height), ref steps)| {
+                // Shrinking can leave anything out of range, so everything is wrapped
+                let (width, height) = (width.max(1), height.max(1));
+                let mut lights = BitGrid::new(width, height);
+                let mut grid = Grid::new(width, height, false);
+                for &(kind, at, by) in steps {
+                    match kind % 3 {
+                        0 => {
+                            let (w, h) = (at % width + 1, by % height + 1);
+                            lights.fill_rect(0, 0, w, h, true);
+                            for (x, y) in grid.positions().collect::<Vec<_>>() {
+                                grid[(x, y)] |= x < w && y < h;
+                            }
+                        }
+                        1 => {
+                            lights.rotate_row(at % height, by);
+                            grid.rotate_row(at % height, by);
+                        }
+                        _ => {
+                            lights.rotate_column(at % width, by);
+                            grid.rotate_column(at % width, by);
+                        }
+                    }
+                }
+                lights.to_grid() == grid
+                    && lights.count_ones() == grid.iter().filter(|(_, &on)| on).count()
+            },
+        )
+    }
+}
@@ -0,0 +1,215 @@
+#![allow(unused)]
+
+// Cycle detection for a process that repeatedly applies `step` to a state. Such a
+// process always ends up going round in a loop once a state comes back, these find where
+// the loop starts and how long it is.
+
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    // The number of states before the loop, the first state in the loop has this index
+    pub tail: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    // The earliest index that holds the same state as index n
+    pub fn reduce(&self, n: u64) -> usize {
+        let tail = self.tail as u64;
+        if n < tail {
+            return n as usize;
+        }
+        (tail + (n - tail) % self.period as u64) as usize
+    }
+}
+
+// Floyd's tortoise and hare, only ever keeps two states around
+pub fn floyd<S: Clone + PartialEq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let ahead = step(&hare);
+        hare = step(&ahead);
+    }
+
+    // The distance from the start to the meeting point is a multiple of the period, so
+    // stepping both at the same speed meets where the loop begins
+    let mut tail = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+    Cycle { tail, period }
+}
+
+// Brent's algorithm, finds the period first and calls step fewer times than Floyd
+pub fn brent<S: Clone + PartialEq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // With the hare a period ahead they meet where the loop begins
+    let mut tail = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+    }
+    Cycle { tail, period }
+}
+
+// Every state up to the point where the first one repeats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<S> {
+    pub cycle: Cycle,
+    // The tail followed by one round of the loop
+    pub states: Vec<S>,
+}
+
+impl<S> History<S> {
+    pub fn nth(&self, n: u64) -> &S {
+        &self.states[self.cycle.reduce(n)]
+    }
+}
+
+// Remembers every state, which costs memory but steps each state only once
+pub fn find_cycle<S: Clone + Eq + Hash>(initial: S, step: impl FnMut(&S) -> S) -> History<S> {
+    match record(initial, step, None) {
+        Ok(history) => history,
+        Err(_) => unreachable!("Without a limit the loop is always found"),
+    }
+}
+
+// Steps until a state repeats or `limit` states are recorded, whichever comes first.
+// Without a repeat the recorded states are returned as they are.
+fn record<S: Clone + Eq + Hash>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    limit: Option<u64>,
+) -> Result<History<S>, Vec<S>> {
+    let mut seen = HashMap::new();
+    let mut states = vec![];
+    let mut state = initial;
+    loop {
+        if let Some(&tail) = seen.get(&state) {
+            let period = states.len() - tail;
+            let cycle = Cycle { tail, period };
+            return Ok(History { cycle, states });
+        }
+        if limit.is_some_and(|limit| states.len() as u64 >= limit) {
+            return Err(states);
+        }
+        let next = step(&state);
+        seen.insert(state.clone(), states.len());
+        states.push(state);
+        state = next;
+    }
+}
+
+// The state after n steps. Goes the long way only until the loop shows up, so this works
+// for a billion steps as long as the loop comes round early.
+pub fn nth_state<S: Clone + Eq + Hash>(initial: S, step: impl FnMut(&S) -> S, n: u64) -> S {
+    match record(initial, step, Some(n.saturating_add(1))) {
+        Ok(history) => history.nth(n).clone(),
+        Err(mut states) => states
+            .pop()
+            .expect("At least the initial state is recorded"),
+    }
+}
+
+#[cfg(test)]
+mod test_cycle {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3, a tail of three and a period of four
+    fn rho(state: &u32) -> u32 {
+        if *state == 6 {
+            3
+        } else {
+            state + 1
+        }
+    }
+
+    #[test]
+    fn test_detectors_agree() {
+        let expected = Cycle { tail: 3, period: 4 };
+        assert_eq!(expected, floyd(0, rho));
+        assert_eq!(expected, brent(0, rho));
+        assert_eq!(expected, find_cycle(0, rho).cycle);
+
+        // Starting inside the loop leaves no tail
+        let inside = Cycle { tail: 0, period: 4 };
+        assert_eq!(inside, floyd(4, rho));
+        assert_eq!(inside, brent(4, rho));
+        assert_eq!(inside, find_cycle(4, rho).cycle);
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let stuck = |_: &char| 'x';
+        let expected = Cycle { tail: 1, period: 1 };
+        assert_eq!(expected, floyd('a', stuck));
+        assert_eq!(expected, brent('a', stuck));
+        assert_eq!(vec!['a', 'x'], find_cycle('a', stuck).states);
+    }
+
+    #[test]
+    fn test_random_functions() {
+        // The squaring map mod a prime has a tail and a loop for most seeds
+        for modulus in [101_u64, 1009, 65_537] {
+            for seed in 2..20 {
+                let square = |v: &u64| v * v % modulus + 1;
+                let history = find_cycle(seed, square);
+                assert_eq!(history.cycle, floyd(seed, square));
+                assert_eq!(history.cycle, brent(seed, square));
+                assert_eq!(
+                    history.cycle.tail + history.cycle.period,
+                    history.states.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nth_state() {
+        let history = find_cycle(0, rho);
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6], history.states);
+        assert_eq!(&2, history.nth(2));
+        assert_eq!(&3, history.nth(7));
+        assert_eq!(&5, history.nth(1_000_000_001));
+
+        assert_eq!(0, nth_state(0, rho, 0));
+        assert_eq!(2, nth_state(0, rho, 2));
+        assert_eq!(5, nth_state(0, rho, 1_000_000_001));
+        assert_eq!(
+            (0..=1_000).fold(0, |state, _| rho(&state)),
+            nth_state(0, rho, 1_001)
+        );
+    }
+}
@@ -0,0 +1,236 @@
+#![allow(unused)]
+
+use std::str::FromStr;
+
+// Compass directions on a grid where y grows downwards, the same way as in Grid. North is
+// up, so its unit vector is (0, -1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    // Clockwise, starting at the top
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    fn rotate(self, quarter_turns: usize) -> Direction {
+        Direction::ALL[(self as usize + quarter_turns) % 4]
+    }
+
+    pub fn turn_left(self) -> Direction {
+        self.rotate(3)
+    }
+
+    pub fn turn_right(self) -> Direction {
+        self.rotate(1)
+    }
+
+    pub fn turn_around(self) -> Direction {
+        self.rotate(2)
+    }
+
+    // Accepts U/D/L/R, N/E/S/W and arrows, both ascii and unicode
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' | 'N' | '^' | '↑' => Some(Direction::North),
+            'R' | 'E' | '>' | '→' => Some(Direction::East),
+            'D' | 'S' | 'v' | '↓' => Some(Direction::South),
+            'L' | 'W' | '<' | '←' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    pub fn unit_vector(self) -> (i64, i64) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Direction, String> {
+        Direction::from_char(c).ok_or_else(|| format!("{c:?} is not a direction"))
+    }
+}
+
+// The eight directions including the diagonals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    // Clockwise, starting at the top
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    fn rotate(self, eighth_turns: usize) -> Direction8 {
+        Direction8::ALL[(self as usize + eighth_turns) % 8]
+    }
+
+    // Turns by 45 degrees
+    pub fn turn_left(self) -> Direction8 {
+        self.rotate(7)
+    }
+
+    // Turns by 45 degrees
+    pub fn turn_right(self) -> Direction8 {
+        self.rotate(1)
+    }
+
+    pub fn turn_around(self) -> Direction8 {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    // Not normalised, the diagonals are (±1, ±1)
+    pub fn unit_vector(self) -> (i64, i64) {
+        match self {
+            Direction8::North => (0, -1),
+            Direction8::NorthEast => (1, -1),
+            Direction8::East => (1, 0),
+            Direction8::SouthEast => (1, 1),
+            Direction8::South => (0, 1),
+            Direction8::SouthWest => (-1, 1),
+            Direction8::West => (-1, 0),
+            Direction8::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Direction8 {
+        Direction8::ALL[direction as usize * 2]
+    }
+}
+
+// Accepts everything a Direction does plus the two letter diagonals like "NE" or "UL"
+impl FromStr for Direction8 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Direction8, String> {
+        let directions = s
+            .chars()
+            .map(Direction::try_from)
+            .collect::<Result<Vec<Direction>, String>>()?;
+
+        match directions[..] {
+            [direction] => Ok(direction.into()),
+            [first, second] if first.is_vertical() != second.is_vertical() => {
+                let (vertical, horizontal) = if first.is_vertical() {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+                let (dx, _) = horizontal.unit_vector();
+                let (_, dy) = vertical.unit_vector();
+                Ok(*Direction8::ALL
+                    .iter()
+                    .find(|d| d.unit_vector() == (dx, dy))
+                    .expect("Every diagonal is in ALL"))
+            }
+            _ => Err(format!("{s:?} is not a direction")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_direction {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::West, Direction::North.turn_left());
+        assert_eq!(Direction::East, Direction::North.turn_right());
+        assert_eq!(Direction::North, Direction::South.turn_around());
+        for direction in Direction::ALL {
+            assert_eq!(direction, direction.turn_left().turn_right());
+            assert_eq!(direction.turn_around(), direction.turn_left().turn_left());
+        }
+
+        assert_eq!(Direction8::NorthWest, Direction8::North.turn_left());
+        assert_eq!(Direction8::SouthEast, Direction8::East.turn_right());
+        assert_eq!(Direction8::NorthEast, Direction8::SouthWest.turn_around());
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = "UN^↑RDLv<"
+            .chars()
+            .map(Direction::from_char)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(Direction::North),
+                Some(Direction::North),
+                Some(Direction::North),
+                Some(Direction::North),
+                Some(Direction::East),
+                Some(Direction::South),
+                Some(Direction::West),
+                Some(Direction::South),
+                Some(Direction::West),
+            ],
+            parsed
+        );
+        assert!(Direction::try_from('x').is_err());
+
+        assert_eq!(Ok(Direction8::South), "S".parse());
+        assert_eq!(Ok(Direction8::NorthEast), "NE".parse());
+        assert_eq!(Ok(Direction8::NorthEast), "EN".parse());
+        assert_eq!(Ok(Direction8::SouthWest), "DL".parse());
+        assert!("NS".parse::<Direction8>().is_err());
+        assert!("".parse::<Direction8>().is_err());
+    }
+
+    #[test]
+    fn test_unit_vectors() {
+        assert_eq!((0, -1), Direction::North.unit_vector());
+        assert_eq!((-1, 0), Direction::West.unit_vector());
+        for direction in Direction::ALL {
+            let (dx, dy) = direction.unit_vector();
+            assert_eq!((-dx, -dy), direction.turn_around().unit_vector());
+            assert_eq!(
+                direction.unit_vector(),
+                Direction8::from(direction).unit_vector()
+            );
+        }
+        assert!(Direction8::SouthWest.is_diagonal());
+        assert!(!Direction8::West.is_diagonal());
+    }
+}
@@ -0,0 +1,220 @@
+#![allow(unused)]
+
+// Region discovery on a Grid. Which cells belong to a region is decided by an `inside`
+// predicate, how they connect by the Connectivity.
+
+use super::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    // Up, down, left and right
+    Four,
+    // Diagonals too
+    Eight,
+}
+
+impl Connectivity {
+    fn neighbours<T>(self, grid: &Grid<T>, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        match self {
+            Connectivity::Four => grid.neighbours4(x, y).collect(),
+            Connectivity::Eight => grid.neighbours8(x, y).collect(),
+        }
+    }
+}
+
+// Marks everything reachable from the seeds through inside cells. Seeds that aren't
+// inside are ignored.
+fn fill_from<T>(
+    grid: &Grid<T>,
+    seeds: impl IntoIterator<Item = (usize, usize)>,
+    connectivity: Connectivity,
+    inside: &mut impl FnMut(&T) -> bool,
+    filled: &mut Grid<bool>,
+) -> Vec<(usize, usize)> {
+    let mut region = Vec::new();
+    let mut stack = Vec::new();
+    for seed in seeds {
+        if !filled[seed] && inside(&grid[seed]) {
+            filled[seed] = true;
+            stack.push(seed);
+        }
+    }
+
+    while let Some(pos) = stack.pop() {
+        region.push(pos);
+        for next in connectivity.neighbours(grid, pos) {
+            if !filled[next] && inside(&grid[next]) {
+                filled[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    region
+}
+
+// Every position connected to the seed, in no particular order. Empty if the seed
+// itself isn't inside.
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    seed: (usize, usize),
+    connectivity: Connectivity,
+    mut inside: impl FnMut(&T) -> bool,
+) -> Vec<(usize, usize)> {
+    let mut filled = Grid::new(grid.width(), grid.height(), false);
+    fill_from(grid, [seed], connectivity, &mut inside, &mut filled)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub size: usize,
+    // The bounding box, both corners inclusive
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    // The index into `components` for every inside cell
+    pub labels: Grid<Option<usize>>,
+    pub components: Vec<Component>,
+}
+
+// Splits the inside cells into connected components, numbered in reading order of their
+// first cell
+pub fn label_components<T>(
+    grid: &Grid<T>,
+    connectivity: Connectivity,
+    mut inside: impl FnMut(&T) -> bool,
+) -> Components {
+    let mut filled = Grid::new(grid.width(), grid.height(), false);
+    let mut labels = Grid::new(grid.width(), grid.height(), None);
+    let mut components = Vec::new();
+
+    for pos in grid.positions() {
+        let region = fill_from(grid, [pos], connectivity, &mut inside, &mut filled);
+        if region.is_empty() {
+            continue;
+        }
+
+        let label = components.len();
+        let mut min = pos;
+        let mut max = pos;
+        for &(x, y) in &region {
+            labels[(x, y)] = Some(label);
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        components.push(Component {
+            size: region.len(),
+            min,
+            max,
+        });
+    }
+    Components { labels, components }
+}
+
+// The inside cells that can be reached from the edge of the grid. The inside cells that
+// are left over are enclosed.
+pub fn reachable_from_border<T>(
+    grid: &Grid<T>,
+    connectivity: Connectivity,
+    mut inside: impl FnMut(&T) -> bool,
+) -> Grid<bool> {
+    let (width, height) = (grid.width(), grid.height());
+    let border = grid
+        .positions()
+        .filter(|&(x, y)| x == 0 || y == 0 || x + 1 == width || y + 1 == height);
+
+    let mut filled = Grid::new(width, height, false);
+    fill_from(grid, border, connectivity, &mut inside, &mut filled);
+    filled
+}
+
+#[cfg(test)]
+mod test_flood {
+    use super::*;
+
+    const MAP: &str = "\
+##..#
+#..##
+.....
+###.#
+#.#.#";
+
+    fn map() -> Grid<char> {
+        Grid::parse(MAP, |c| c).unwrap()
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = map();
+        let mut region = flood_fill(&grid, (0, 0), Connectivity::Four, |&c| c == '#');
+        region.sort();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 0)], region);
+
+        let diagonal = Grid::parse("#.\n.#", |c| c).unwrap();
+        let region = flood_fill(&diagonal, (0, 0), Connectivity::Four, |&c| c == '#');
+        assert_eq!(1, region.len());
+        let region = flood_fill(&diagonal, (0, 0), Connectivity::Eight, |&c| c == '#');
+        assert_eq!(2, region.len());
+
+        assert!(flood_fill(&grid, (2, 2), Connectivity::Four, |&c| c == '#').is_empty());
+        assert_eq!(
+            11,
+            flood_fill(&grid, (2, 2), Connectivity::Four, |&c| c == '.').len()
+        );
+    }
+
+    #[test]
+    fn test_label_components() {
+        let grid = map();
+        let Components { labels, components } =
+            label_components(&grid, Connectivity::Four, |&c| c == '#');
+
+        assert_eq!(4, components.len());
+        assert_eq!(
+            Component {
+                size: 3,
+                min: (0, 0),
+                max: (1, 1),
+            },
+            components[0]
+        );
+        assert_eq!(
+            Component {
+                size: 5,
+                min: (0, 3),
+                max: (2, 4),
+            },
+            components[2]
+        );
+        assert_eq!(Some(1), labels[(3, 1)]);
+        assert_eq!(None, labels[(2, 2)]);
+
+        // The open cells, where (1, 4) is walled in even diagonally
+        let open = label_components(&grid, Connectivity::Eight, |&c| c == '.');
+        assert_eq!(
+            vec![11, 1],
+            open.components.iter().map(|c| c.size).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ((1, 4), (1, 4)),
+            (open.components[1].min, open.components[1].max)
+        );
+    }
+
+    #[test]
+    fn test_reachable_from_border() {
+        let grid = Grid::parse("#####\n#...#\n#.#.#\n#...#\n##.##", |c| c).unwrap();
+        let reachable = reachable_from_border(&grid, Connectivity::Four, |&c| c == '.');
+        let enclosed = grid
+            .iter()
+            .filter(|&(pos, &c)| c == '.' && !reachable[pos])
+            .count();
+        assert_eq!(0, enclosed);
+
+        let grid = Grid::parse("#####\n#...#\n#.#.#\n#...#\n#####", |c| c).unwrap();
+        let reachable = reachable_from_border(&grid, Connectivity::Four, |&c| c == '.');
+        assert!(grid.positions().all(|pos| !reachable[pos]));
+    }
+}
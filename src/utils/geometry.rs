@@ -0,0 +1,162 @@
+#![allow(unused)]
+
+// Areas of closed paths on the integer lattice. Everything is done in i128, which is
+// enough for any polygon with i64 coordinates whose doubled area fits in an i128, like a
+// square with sides of i64::MAX.
+
+use super::point::Point2;
+
+// Twice the signed area, positive when the vertices go counter clockwise in a y up
+// coordinate system
+fn double_area(vertices: &[Point2<i64>]) -> i128 {
+    let Some(&last) = vertices.last() else {
+        return 0;
+    };
+    let mut previous = last;
+    let mut sum = 0_i128;
+    for &vertex in vertices {
+        sum += previous.x as i128 * vertex.y as i128 - vertex.x as i128 * previous.y as i128;
+        previous = vertex;
+    }
+    sum
+}
+
+// The shoelace formula. The path is closed, the last vertex connects back to the first.
+pub fn shoelace_area(vertices: &[Point2<i64>]) -> i128 {
+    double_area(vertices).abs() / 2
+}
+
+// The lattice points on the edges. For rectilinear edges that is just their length.
+pub fn boundary_points(vertices: &[Point2<i64>]) -> i128 {
+    let Some(&last) = vertices.last() else {
+        return 0;
+    };
+    let mut previous = last;
+    let mut count = 0_i128;
+    for &vertex in vertices {
+        let dx = (vertex.x as i128 - previous.x as i128).abs();
+        let dy = (vertex.y as i128 - previous.y as i128).abs();
+        count += num::integer::gcd(dx, dy);
+        previous = vertex;
+    }
+    count
+}
+
+// Pick's theorem, A = i + b/2 - 1, solved for the points strictly inside. A path that
+// encloses no area, like a line walked there and back, has nothing inside it rather
+// than the negative count the formula gives.
+pub fn interior_points(vertices: &[Point2<i64>]) -> i128 {
+    let double = double_area(vertices).abs();
+    let boundary = boundary_points(vertices);
+    if boundary == 0 {
+        return 0;
+    }
+    ((double - boundary) / 2 + 1).max(0)
+}
+
+// The corners of a path given as unit steps from `start`. Steps along a straight line
+// are merged, so only the points where the path turns are returned.
+pub fn vertices_from_steps(
+    start: Point2<i64>,
+    steps: impl IntoIterator<Item = Point2<i64>>,
+) -> Vec<Point2<i64>> {
+    let mut vertices = Vec::new();
+    let mut position = start;
+    let mut first_heading = None;
+    let mut heading = None;
+    for step in steps {
+        if heading != Some(step) {
+            vertices.push(position);
+        }
+        first_heading.get_or_insert(step);
+        heading = Some(step);
+        position += step;
+    }
+    // A closed path that leaves the start the way it came in doesn't turn there
+    if position == start && heading == first_heading && vertices.len() > 1 {
+        vertices.remove(0);
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod test_geometry {
+    use super::*;
+
+    fn points(coords: &[(i64, i64)]) -> Vec<Point2<i64>> {
+        coords.iter().map(|&pos| pos.into()).collect()
+    }
+
+    #[test]
+    fn test_square() {
+        let square = points(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(16, shoelace_area(&square));
+        assert_eq!(16, boundary_points(&square));
+        assert_eq!(9, interior_points(&square));
+
+        // The direction the path goes round in doesn't matter
+        let reversed = square.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(16, shoelace_area(&reversed));
+        assert_eq!(9, interior_points(&reversed));
+    }
+
+    #[test]
+    fn test_rectilinear() {
+        // An L shape
+        let shape = points(&[(0, 0), (3, 0), (3, 1), (1, 1), (1, 3), (0, 3)]);
+        assert_eq!(5, shoelace_area(&shape));
+        assert_eq!(12, boundary_points(&shape));
+        assert_eq!(0, interior_points(&shape));
+    }
+
+    #[test]
+    fn test_diagonal_edges() {
+        let triangle = points(&[(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(8, shoelace_area(&triangle));
+        assert_eq!(12, boundary_points(&triangle));
+        assert_eq!(3, interior_points(&triangle));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(0, shoelace_area(&[]));
+        assert_eq!(0, interior_points(&[]));
+        assert_eq!(0, interior_points(&points(&[(5, 5)])));
+        assert_eq!(0, interior_points(&points(&[(0, 0), (2, 0)])));
+        assert_eq!(
+            0,
+            interior_points(&points(&[(0, 0), (4, 0), (4, 3), (4, 0)]))
+        );
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let max = i64::MAX;
+        let square = points(&[(0, 0), (max, 0), (max, max), (0, max)]);
+        let side = max as i128;
+        assert_eq!(side * side, shoelace_area(&square));
+        assert_eq!(4 * side, boundary_points(&square));
+        assert_eq!((side - 1) * (side - 1), interior_points(&square));
+    }
+
+    #[test]
+    fn test_vertices_from_steps() {
+        let (right, down, left, up) = (
+            Point2::new(1, 0),
+            Point2::new(0, 1),
+            Point2::new(-1, 0),
+            Point2::new(0, -1),
+        );
+        // A 2x2 square walked from the middle of its top edge
+        let steps = [right, down, down, left, left, up, up, right];
+        let vertices = vertices_from_steps(Point2::new(1, 0), steps);
+        assert_eq!(points(&[(2, 0), (2, 2), (0, 2), (0, 0)]), vertices);
+        assert_eq!(4, shoelace_area(&vertices));
+        assert_eq!(1, interior_points(&vertices));
+
+        // Walked from a corner, which stays a vertex
+        let steps = [right, right, down, down, left, left, up, up];
+        let vertices = vertices_from_steps(Point2::origin(), steps);
+        assert_eq!(points(&[(0, 0), (2, 0), (2, 2), (0, 2)]), vertices);
+    }
+}
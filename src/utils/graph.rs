@@ -0,0 +1,322 @@
+#![allow(unused)]
+
+// A directed graph over any hashable keys. Keys are interned to dense ids, so walking
+// the graph is indexing into adjacency lists instead of hashing a key every step. Edges
+// carry a label, like the left or right choice at a fork. When nothing tells edges apart
+// () does, or "" for a graph that gets drawn.
+
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{Display, Write},
+    hash::Hash,
+};
+
+use super::interner::Interner;
+
+#[derive(Debug, Clone)]
+pub struct Graph<K, L> {
+    nodes: Interner<K>,
+    // The outgoing edges of every node as (target, label)
+    edges: Vec<Vec<(usize, L)>>,
+}
+
+impl<K, L> Default for Graph<K, L> {
+    fn default() -> Graph<K, L> {
+        Graph {
+            nodes: Interner::default(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, L> Graph<K, L> {
+    pub fn new() -> Graph<K, L> {
+        Graph::default()
+    }
+
+    // The node's id, added without edges if it is new
+    pub fn add_node(&mut self, key: K) -> usize {
+        let id = self.nodes.intern(key);
+        if id == self.edges.len() {
+            self.edges.push(Vec::new());
+        }
+        id
+    }
+
+    // Adds the nodes too if they are new. Returns their ids.
+    pub fn add_edge(&mut self, from: K, to: K, label: L) -> (usize, usize) {
+        let (from, to) = (self.add_node(from), self.add_node(to));
+        self.edges[from].push((to, label));
+        (from, to)
+    }
+
+    pub fn id<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.nodes.id(key)
+    }
+
+    pub fn key(&self, id: usize) -> &K {
+        self.nodes.key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+
+    // Every node as (id, key), in id order
+    pub fn nodes(&self) -> impl Iterator<Item = (usize, &K)> {
+        self.nodes.keys().enumerate()
+    }
+
+    pub fn edges(&self, id: usize) -> &[(usize, L)] {
+        &self.edges[id]
+    }
+
+    pub fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges[id].iter().map(|&(to, _)| to)
+    }
+
+    // Where the first edge with the label leads
+    pub fn follow(&self, id: usize, label: &L) -> Option<usize>
+    where
+        L: PartialEq,
+    {
+        self.edges[id]
+            .iter()
+            .find(|(_, l)| l == label)
+            .map(|&(to, _)| to)
+    }
+
+    // Tarjan's algorithm, with an explicit stack so long chains don't overflow. Every
+    // component comes before the components it has edges to, and the nodes in a
+    // component are in increasing id order.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+        let mut index = vec![UNVISITED; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+
+        for root in 0..self.len() {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            // The nodes being visited, each with how many of its edges were looked at
+            let mut path = vec![(root, 0)];
+            index[root] = next_index;
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&mut (node, ref mut edge)) = path.last_mut() {
+                if let Some(&(to, _)) = self.edges[node].get(*edge) {
+                    *edge += 1;
+                    if index[to] == UNVISITED {
+                        index[to] = next_index;
+                        low_link[to] = next_index;
+                        next_index += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        path.push((to, 0));
+                    } else if on_stack[to] {
+                        low_link[node] = low_link[node].min(index[to]);
+                    }
+                    continue;
+                }
+
+                path.pop();
+                if let Some(&(parent, _)) = path.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+
+        // Tarjan finds them sinks first
+        components.reverse();
+        components
+    }
+
+    // Kahn's algorithm, with ties going to the lowest id. None if there is a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0; self.len()];
+        for to in (0..self.len()).flat_map(|id| self.successors(id)) {
+            in_degree[to] += 1;
+        }
+        let mut ready = BinaryHeap::new();
+        ready.extend(
+            (0..self.len())
+                .filter(|&id| in_degree[id] == 0)
+                .map(Reverse),
+        );
+
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(Reverse(id)) = ready.pop() {
+            order.push(id);
+            for to in self.successors(id) {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    ready.push(Reverse(to));
+                }
+            }
+        }
+        (order.len() == self.len()).then_some(order)
+    }
+
+    // Graphviz source for the graph. Labels that display as nothing are left off.
+    pub fn to_dot(&self, name: &str) -> String
+    where
+        K: Display,
+        L: Display,
+    {
+        let mut dot = format!("digraph {} {{\n", quoted(name));
+        for (_, key) in self.nodes() {
+            writeln!(dot, "    {};", quoted(key)).unwrap();
+        }
+        for (from, key) in self.nodes() {
+            for (to, label) in self.edges(from) {
+                let (from, to) = (quoted(key), quoted(self.key(*to)));
+                write!(dot, "    {from} -> {to}").unwrap();
+                let label = label.to_string();
+                if !label.is_empty() {
+                    write!(dot, " [label={}]", quoted(&label)).unwrap();
+                }
+                dot.push_str(";\n");
+            }
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+fn quoted(text: impl Display) -> String {
+    format!(
+        "\"{}\"",
+        text.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+#[cfg(test)]
+mod test_graph {
+    use super::*;
+
+    fn graph(edges: &[(&'static str, &'static str)]) -> Graph<&'static str, &'static str> {
+        let mut graph = Graph::new();
+        for &(from, to) in edges {
+            graph.add_edge(from, to, "");
+        }
+        graph
+    }
+
+    #[test]
+    fn test_interning_and_labels() {
+        let mut graph = Graph::new();
+        graph.add_edge("AAA", "BBB", 'L');
+        graph.add_edge("AAA", "CCC", 'R');
+        graph.add_edge("BBB", "AAA", 'L');
+        assert_eq!(3, graph.len());
+        assert_eq!(3, graph.edge_count());
+        let (aaa, ccc) = (graph.id("AAA").unwrap(), graph.id("CCC").unwrap());
+        assert_eq!(Some(ccc), graph.follow(aaa, &'R'));
+        assert_eq!(None, graph.follow(ccc, &'R'));
+        assert_eq!(&"BBB", graph.key(graph.follow(aaa, &'L').unwrap()));
+        assert_eq!(
+            vec![(0, &"AAA"), (1, &"BBB"), (2, &"CCC")],
+            graph.nodes().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // a <-> b -> c -> d -> e -> c, and f on its own
+        let mut graph = graph(&[
+            ("a", "b"),
+            ("b", "a"),
+            ("b", "c"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "c"),
+        ]);
+        graph.add_node("f");
+        let components = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|component| component.into_iter().map(|id| *graph.key(id)).collect())
+            .collect::<Vec<Vec<_>>>();
+        assert_eq!(
+            vec![vec!["f"], vec!["a", "b"], vec!["c", "d", "e"]],
+            components
+        );
+    }
+
+    #[test]
+    fn test_long_chain_does_not_overflow() {
+        let mut graph = Graph::new();
+        for i in 0..100_000 {
+            graph.add_edge(i, i + 1, ());
+        }
+        graph.add_edge(100_000, 0, ());
+        assert_eq!(1, graph.strongly_connected_components().len());
+        assert_eq!(None, graph.topological_sort());
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let graph = graph(&[
+            ("shirt", "tie"),
+            ("tie", "jacket"),
+            ("trousers", "shoes"),
+            ("shirt", "belt"),
+            ("trousers", "belt"),
+            ("belt", "jacket"),
+        ]);
+        let order = graph
+            .topological_sort()
+            .unwrap()
+            .into_iter()
+            .map(|id| *graph.key(id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["shirt", "tie", "trousers", "shoes", "belt", "jacket"],
+            order
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Graph::new();
+        graph.add_edge("AAA", "BBB", "L");
+        graph.add_edge("AAA", "say \"hi\"", "");
+        assert_eq!(
+            "digraph \"day8\" {\n    \"AAA\";\n    \"BBB\";\n    \"say \\\"hi\\\"\";\n    \"AAA\" -> \"BBB\" [label=\"L\"];\n    \"AAA\" -> \"say \\\"hi\\\"\";\n}",
+            graph.to_dot("day8")
+        );
+    }
+}
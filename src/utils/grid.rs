@@ -0,0 +1,360 @@
+#![allow(unused)]
+
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+const NEIGHBOURS_4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOURS_8: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+// A dense rectangular grid stored row by row. Positions are (x, y) with x going right
+// and y going down from the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut cell: impl FnMut(usize, usize) -> T,
+    ) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| cell(x, y))
+                .collect(),
+        }
+    }
+
+    // Fails if the rows don't all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(format!(
+                "Row {} has {} cells, expected {width}",
+                y + 1,
+                rows[y].len()
+            ));
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    // Every line of the text is a row and every character a cell. Trailing newlines and
+    // CRLF line endings are fine, lines of different lengths are not.
+    pub fn try_parse(
+        input: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, String> {
+        let rows = input
+            .lines()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| {
+                        cell(c).ok_or_else(|| format!("Unexpected {c:?} at x={x}, y={y}"))
+                    })
+                    .collect::<Result<Vec<T>, String>>()
+            })
+            .collect::<Result<Vec<Vec<T>>, String>>()?;
+        Grid::from_rows(rows)
+    }
+
+    pub fn parse(input: &str, mut cell: impl FnMut(char) -> T) -> Result<Grid<T>, String> {
+        Grid::try_parse(input, |c| Some(cell(c)))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.contains(x, y).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.contains(x, y)
+            .then(|| &mut self.cells[y * self.width + x])
+    }
+
+    // Moves from a position by an offset, None when that leaves the grid
+    pub fn step(&self, x: usize, y: usize, dx: i64, dy: i64) -> Option<(usize, usize)> {
+        let nx = usize::try_from(x as i64 + dx).ok()?;
+        let ny = usize::try_from(y as i64 + dy).ok()?;
+        self.contains(nx, ny).then_some((nx, ny))
+    }
+
+    // Neighbours up, right, down and left, skipping those outside the grid
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&(dx, dy)| self.step(x, y, dx, dy))
+    }
+
+    // Neighbours including the diagonals, clockwise from the top left
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&(dx, dy)| self.step(x, y, dx, dy))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {y} is outside the grid");
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    // Rows of a grid with no columns are still there, they are just empty
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {x} is outside the grid");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |x, y| self[(y, x)].clone())
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |x, y| {
+            self[(y, self.height - 1 - x)].clone()
+        })
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.height, self.width, |x, y| {
+            self[(self.width - 1 - y, x)].clone()
+        })
+    }
+
+    // Shifts a row to the right, cells falling off the end come back on the left
+    pub fn rotate_row(&mut self, y: usize, by: usize) {
+        assert!(y < self.height, "row {y} is outside the grid");
+        let width = self.width;
+        let by = by.checked_rem(width).unwrap_or(0);
+        self.cells[y * width..(y + 1) * width].rotate_right(by);
+    }
+
+    // Shifts a column down, cells falling off the bottom come back at the top
+    pub fn rotate_column(&mut self, x: usize, by: usize)
+    where
+        T: Clone,
+    {
+        let column = self.column(x).cloned().collect_vec();
+        let by = by.checked_rem(self.height).unwrap_or(0);
+        for (y, cell) in column.into_iter().enumerate() {
+            let y = (y + by) % self.height;
+            self[(x, y)] = cell;
+        }
+    }
+
+    // Renders every cell as a single character, one line per row
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&mut cell).collect::<String>())
+            .join("\n")
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(self.contains(x, y), "({x}, {y}) is outside the grid");
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(self.contains(x, y), "({x}, {y}) is outside the grid");
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            row.iter().try_for_each(|cell| write!(f, "{cell}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_grid {
+    use super::*;
+    use crate::utils::prop::catch_panic;
+
+    fn letters() -> Grid<char> {
+        Grid::parse("abc\ndef", |c| c).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = letters();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(Some(&'f'), grid.get(2, 1));
+        assert_eq!(None, grid.get(3, 0));
+        assert_eq!('d', grid[(0, 1)]);
+
+        assert_eq!(Ok(grid), Grid::parse("abc\r\ndef\r\n", |c| c));
+        assert!(Grid::parse("abc\nde", |c| c).is_err());
+        assert!(Grid::try_parse("#.#\n.?.", |c| (c != '?').then_some(c)).is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(vec![(1, 0), (0, 1)], grid.neighbours4(0, 0).collect_vec());
+        assert_eq!(3, grid.neighbours8(2, 2).count());
+        assert_eq!(8, grid.neighbours8(1, 1).count());
+        assert_eq!(None, grid.step(0, 1, -1, 0));
+        assert_eq!(Some((2, 0)), grid.step(0, 1, 2, -1));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = letters();
+        assert_eq!(
+            vec![&['a', 'b', 'c'][..], &['d', 'e', 'f'][..]],
+            grid.rows().collect_vec()
+        );
+        assert_eq!(vec![&'b', &'e'], grid.column(1).collect_vec());
+        assert_eq!(3, grid.columns().count());
+        assert_eq!(Some((1, 1)), grid.position(|&c| c == 'e'));
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let grid = letters();
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!("da\neb\nfc", grid.rotate_clockwise().to_string());
+        assert_eq!("cf\nbe\nad", grid.rotate_counter_clockwise().to_string());
+        assert_eq!(grid, grid.rotate_clockwise().rotate_counter_clockwise());
+    }
+
+    #[test]
+    fn test_rotate_row_and_column() {
+        let mut grid = letters();
+        grid.rotate_row(0, 4);
+        assert_eq!("cab\ndef", grid.to_string());
+        grid.rotate_column(2, 1);
+        assert_eq!("caf\ndeb", grid.to_string());
+        grid.rotate_column(0, usize::MAX);
+        assert_eq!("daf\nceb", grid.to_string());
+    }
+
+    #[test]
+    fn test_out_of_range_rows_and_columns() {
+        let grid = letters();
+        assert_eq!(vec![&'c', &'f'], grid.column(2).collect::<Vec<_>>());
+        assert!(catch_panic(|| grid.column(3).count()).is_err());
+        assert!(catch_panic(|| grid.row(2).len()).is_err());
+        assert!(catch_panic(|| letters().rotate_row(2, 1)).is_err());
+        assert!(catch_panic(|| letters().rotate_column(3, 1)).is_err());
+
+        // Nothing to rotate, but no division by zero either
+        let mut empty = Grid::new(0, 2, 'x');
+        empty.rotate_row(1, 5);
+        assert_eq!(0, empty.columns().count());
+        assert_eq!(vec![&[] as &[char]; 2], empty.rows().collect::<Vec<_>>());
+        assert!(catch_panic(|| empty.row(2).len()).is_err());
+    }
+
+    #[test]
+    fn test_map_and_render() {
+        let grid = Grid::parse("#.\n.#", |c| c == '#').unwrap();
+        assert_eq!("X \n X", grid.render(|&on| if on { 'X' } else { ' ' }));
+        assert_eq!(
+            2,
+            grid.map(|&on| on as u32)
+                .iter()
+                .map(|(_, n)| n)
+                .sum::<u32>()
+        );
+    }
+}
@@ -0,0 +1,82 @@
+#![allow(unused)]
+
+// Hands out dense ids for keys, in the order the keys are first seen, so structures that
+// work on indices can be used with names or positions.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+#[derive(Debug, Clone)]
+pub struct Interner<K> {
+    ids: HashMap<K, usize>,
+    keys: Vec<K>,
+}
+
+impl<K> Default for Interner<K> {
+    fn default() -> Interner<K> {
+        Interner {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> Interner<K> {
+    pub fn new() -> Interner<K> {
+        Interner::default()
+    }
+
+    // The id of the key, a new one if it wasn't seen before
+    pub fn intern(&mut self, key: K) -> usize {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.keys.len();
+        self.keys.push(key.clone());
+        self.ids.insert(key, id);
+        id
+    }
+
+    pub fn id<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(key).copied()
+    }
+
+    // Panics on ids this interner didn't hand out
+    pub fn key(&self, id: usize) -> &K {
+        &self.keys[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // The keys in id order
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.keys.iter()
+    }
+}
+
+#[cfg(test)]
+mod test_interner {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_dense_and_stable() {
+        let mut interner = Interner::new();
+        assert_eq!(0, interner.intern("AAA".to_string()));
+        assert_eq!(1, interner.intern("BBB".to_string()));
+        assert_eq!(0, interner.intern("AAA".to_string()));
+        assert_eq!(Some(1), interner.id("BBB"));
+        assert_eq!(None, interner.id("ZZZ"));
+        assert_eq!("BBB", interner.key(1));
+        assert_eq!(vec!["AAA", "BBB"], interner.keys().collect::<Vec<_>>());
+        assert_eq!(2, interner.len());
+    }
+}
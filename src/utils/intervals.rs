@@ -0,0 +1,420 @@
+#![allow(unused)]
+
+// Sets of integers stored as half open ranges, and maps that shift parts of the number
+// line by an offset. Ranges are Range<i64>, so i64::MAX itself can never be in a set.
+
+use std::ops::Range;
+
+// Sorted ranges that neither overlap nor touch, and none of them is empty
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    // Sorts the ranges and merges the ones that overlap or touch
+    fn normalize(mut ranges: Vec<Range<i64>>) -> IntervalSet {
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<i64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = IntervalSet::normalize(ranges);
+    }
+
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        // The last range that starts at or before the value
+        let idx = self.ranges.partition_point(|range| range.start <= value);
+        idx > 0 && value < self.ranges[idx - 1].end
+    }
+
+    // The number of integers in the set. A u64 because 0..i64::MAX and friends don't fit
+    // the other way round.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end.abs_diff(range.start))
+            .sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    // The largest value in the set, one less than the end of the last range
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|range| range.end - 1)
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::normalize(self.ranges.iter().chain(&other.ranges).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        // Both are sorted, so walk them side by side
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let mut others = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut start = range.start;
+            // Ranges of the other set that end before this one starts can't matter anymore
+            while others.next_if(|o| o.end <= start).is_some() {}
+            for cut in others.clone().take_while(|o| o.start < range.end) {
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> IntervalSet {
+        IntervalSet::normalize(iter.into_iter().collect())
+    }
+}
+
+impl From<Range<i64>> for IntervalSet {
+    fn from(range: Range<i64>) -> IntervalSet {
+        IntervalSet::normalize(vec![range])
+    }
+}
+
+// Moves a range, panics if that pushes it out of i64
+fn shift(range: &Range<i64>, offset: i64) -> Range<i64> {
+    let moved = |value: i64| {
+        value
+            .checked_add(offset)
+            .expect("The offset moves the range out of i64")
+    };
+    moved(range.start)..moved(range.end)
+}
+
+// Shifts every value in a piece by the offset of that piece, everything outside the
+// pieces stays where it is
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeMap {
+    // Sorted and disjoint, every range covered by exactly one piece
+    pieces: Vec<(Range<i64>, i64)>,
+}
+
+impl RangeMap {
+    pub fn new() -> RangeMap {
+        RangeMap::default()
+    }
+
+    // Adds a piece for the part of the range that isn't covered yet, so the first piece
+    // inserted for a value wins
+    pub fn insert(&mut self, range: Range<i64>, offset: i64) {
+        let covered = self.domain();
+        let uncovered = IntervalSet::from(range).difference(&covered);
+        self.pieces
+            .extend(uncovered.ranges.into_iter().map(|range| (range, offset)));
+        self.pieces.sort_by_key(|(range, _)| range.start);
+    }
+
+    pub fn pieces(&self) -> &[(Range<i64>, i64)] {
+        &self.pieces
+    }
+
+    // The values that have a piece, including pieces with an offset of zero
+    pub fn domain(&self) -> IntervalSet {
+        self.pieces.iter().map(|(range, _)| range.clone()).collect()
+    }
+
+    fn offset_of(&self, value: i64) -> i64 {
+        let idx = self
+            .pieces
+            .partition_point(|(range, _)| range.start <= value);
+        match idx.checked_sub(1).map(|idx| &self.pieces[idx]) {
+            Some((range, offset)) if value < range.end => *offset,
+            _ => 0,
+        }
+    }
+
+    pub fn apply(&self, value: i64) -> i64 {
+        value + self.offset_of(value)
+    }
+
+    // Maps every value in the set at once
+    pub fn apply_set(&self, set: &IntervalSet) -> IntervalSet {
+        let mut mapped = set.difference(&self.domain()).ranges;
+        for (range, offset) in &self.pieces {
+            let hit = set.intersection(&IntervalSet::from(range.clone()));
+            mapped.extend(hit.ranges.iter().map(|range| shift(range, *offset)));
+        }
+        IntervalSet::normalize(mapped)
+    }
+
+    // The map that applies self first and then `next`
+    pub fn compose(&self, next: &RangeMap) -> RangeMap {
+        let mut pieces = Vec::new();
+        // Every stretch self maps as one block, including the untouched parts
+        let untouched = IntervalSet::from(i64::MIN..i64::MAX).difference(&self.domain());
+        let blocks = self
+            .pieces
+            .iter()
+            .cloned()
+            .chain(untouched.ranges.into_iter().map(|range| (range, 0)));
+
+        for (range, offset) in blocks {
+            let image = IntervalSet::from(shift(&range, offset));
+            // Where next moves the image, and where it leaves it alone
+            let still = image.difference(&next.domain());
+            let moved = next.pieces.iter().flat_map(|(next_range, next_offset)| {
+                image
+                    .intersection(&IntervalSet::from(next_range.clone()))
+                    .ranges
+                    .into_iter()
+                    .map(move |part| (part, *next_offset))
+            });
+            for (part, next_offset) in still.ranges.into_iter().map(|part| (part, 0)).chain(moved) {
+                let total = offset + next_offset;
+                if total != 0 {
+                    pieces.push((shift(&part, -offset), total));
+                }
+            }
+        }
+        pieces.sort_by_key(|(range, _)| range.start);
+        RangeMap { pieces }
+    }
+
+    // The map that undoes this one, None if two values end up in the same place
+    pub fn invert(&self) -> Option<RangeMap> {
+        let images = self
+            .pieces
+            .iter()
+            .map(|(range, offset)| shift(range, *offset))
+            .collect::<Vec<Range<i64>>>();
+        let image_set = images.iter().cloned().collect::<IntervalSet>();
+
+        // The pieces have to land on each other without overlapping, otherwise the
+        // values they move collide with each other or with the untouched ones
+        let overlapping = image_set.len() != self.domain().len();
+        if overlapping || image_set != self.domain() {
+            return None;
+        }
+
+        let mut pieces = images
+            .into_iter()
+            .zip(&self.pieces)
+            .map(|(image, (_, offset))| (image, -offset))
+            .collect::<Vec<_>>();
+        pieces.sort_by_key(|(range, _)| range.start);
+        Some(RangeMap { pieces })
+    }
+}
+
+impl FromIterator<(Range<i64>, i64)> for RangeMap {
+    fn from_iter<I: IntoIterator<Item = (Range<i64>, i64)>>(iter: I) -> RangeMap {
+        let mut map = RangeMap::new();
+        for (range, offset) in iter {
+            map.insert(range, offset);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test_intervals {
+    use super::*;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(&[0..5, 7..9], set(&[3..5, 7..8, 0..3, 8..9, 4..4]).ranges());
+        assert!(set(&[5..5, Range { start: 6, end: 2 }]).is_empty());
+        assert_eq!(IntervalSet::new(), set(&[]));
+
+        let mut touching = IntervalSet::from(0..2);
+        touching.insert(2..4);
+        assert_eq!(IntervalSet::from(0..4), touching);
+    }
+
+    #[test]
+    fn test_contains_and_len() {
+        let numbers = set(&[-5..-2, 10..12]);
+        assert!(numbers.contains(-5) && numbers.contains(11));
+        assert!(!numbers.contains(-2) && !numbers.contains(12) && !numbers.contains(0));
+        assert_eq!(5, numbers.len());
+        assert_eq!((Some(-5), Some(11)), (numbers.min(), numbers.max()));
+        assert_eq!(
+            (None, None),
+            (IntervalSet::new().min(), IntervalSet::new().max())
+        );
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 30..35]);
+        assert_eq!(IntervalSet::from(0..35), a.union(&b));
+        assert_eq!(&[5..10, 20..25], a.intersection(&b).ranges());
+        assert_eq!(&[0..5, 25..30], a.difference(&b).ranges());
+        assert_eq!(&[10..20, 30..35], b.difference(&a).ranges());
+
+        // Touching ranges share no values
+        assert!(IntervalSet::from(0..5)
+            .intersection(&IntervalSet::from(5..9))
+            .is_empty());
+        assert_eq!(
+            IntervalSet::from(0..5),
+            IntervalSet::from(0..5).difference(&IntervalSet::from(5..9))
+        );
+
+        let empty = IntervalSet::new();
+        assert_eq!(a, a.union(&empty));
+        assert_eq!(empty, a.intersection(&empty));
+        assert_eq!(a, a.difference(&empty));
+        assert_eq!(empty, empty.difference(&a));
+    }
+
+    #[test]
+    fn test_extreme_ranges() {
+        let everything = IntervalSet::from(i64::MIN..i64::MAX);
+        assert_eq!(u64::MAX, everything.len());
+        assert!(everything.contains(i64::MIN) && !everything.contains(i64::MAX));
+
+        let high = IntervalSet::from(10..i64::MAX);
+        assert_eq!(
+            IntervalSet::from(0..i64::MAX),
+            high.union(&IntervalSet::from(0..10))
+        );
+        assert_eq!(
+            IntervalSet::from(i64::MIN..10),
+            everything.difference(&high)
+        );
+        assert_eq!(high, everything.intersection(&high));
+        assert_eq!((i64::MAX - 10) as u64, high.len());
+    }
+
+    fn seed_to_soil() -> RangeMap {
+        [(98..100, -48), (50..98, 2)].into_iter().collect()
+    }
+
+    #[test]
+    fn test_range_map_apply() {
+        let map = seed_to_soil();
+        assert_eq!(
+            vec![81, 14, 57, 13, 50, 52, 99, 100],
+            [79, 14, 55, 13, 98, 50, 97, 100].map(|v| map.apply(v))
+        );
+
+        let mapped = map.apply_set(&set(&[45..55, 95..101]));
+        // 50..55 moves up against the untouched 45..50, 98..100 down into it
+        assert_eq!(&[45..57, 97..101], mapped.ranges());
+        assert_eq!(16, mapped.len());
+        assert_eq!(IntervalSet::new(), map.apply_set(&IntervalSet::new()));
+    }
+
+    #[test]
+    fn test_first_piece_wins() {
+        let map: RangeMap = [(0..10, 5), (5..15, 100)].into_iter().collect();
+        assert_eq!(12, map.apply(7));
+        assert_eq!(112, map.apply(12));
+        assert_eq!(&[(0..10, 5), (10..15, 100)], map.pieces());
+    }
+
+    #[test]
+    fn test_compose() {
+        let first = seed_to_soil();
+        let second: RangeMap = [(15..52, -15), (52..54, -15), (0..15, 39)]
+            .into_iter()
+            .collect();
+        let both = first.compose(&second);
+        for value in -5..110 {
+            assert_eq!(
+                second.apply(first.apply(value)),
+                both.apply(value),
+                "{value}"
+            );
+        }
+
+        // Pieces that cancel out disappear, 98..100 is only moved by the second map
+        let there: RangeMap = [(50..98, 2)].into_iter().collect();
+        let back: RangeMap = [(52..100, -2)].into_iter().collect();
+        assert_eq!(&[(98..100, -2)], there.compose(&back).pieces());
+    }
+
+    #[test]
+    fn test_compose_near_max() {
+        let up: RangeMap = [(0..10, i64::MAX - 10)].into_iter().collect();
+        let down: RangeMap = [(i64::MAX - 10..i64::MAX, -(i64::MAX - 10))]
+            .into_iter()
+            .collect();
+        let both = up.compose(&down);
+        assert_eq!(
+            &[(i64::MAX - 10..i64::MAX, -(i64::MAX - 10))],
+            both.pieces()
+        );
+        assert_eq!(i64::MAX - 1, up.apply(9));
+        assert_eq!(9, both.apply(9));
+        assert_eq!(9, both.apply(i64::MAX - 1));
+    }
+
+    #[test]
+    fn test_invert() {
+        // Swaps 0..10 and 10..20
+        let swap: RangeMap = [(0..10, 10), (10..20, -10)].into_iter().collect();
+        let inverse = swap.invert().expect("A swap is a bijection");
+        for value in -5..25 {
+            assert_eq!(value, inverse.apply(swap.apply(value)));
+        }
+
+        // Moves 0..10 onto values that stay put
+        assert_eq!(None, RangeMap::from_iter([(0..10, 10)]).invert());
+        // Two pieces on top of each other
+        assert_eq!(
+            None,
+            RangeMap::from_iter([(0..10, 10), (10..20, 0), (20..30, -10)]).invert()
+        );
+        assert_eq!(Some(RangeMap::new()), RangeMap::new().invert());
+    }
+}
@@ -0,0 +1,174 @@
+#![allow(unused)]
+
+// Searching for the indices whose MD5 of `salt + index` has some property, usually a
+// run of leading zeros in hex. The salt is hashed once and its state copied for every
+// index, the index is written as digits on the stack and digests are checked as raw
+// bytes, so nothing is allocated per hash.
+
+use std::collections::VecDeque;
+
+use rayon::prelude::*;
+
+pub type Digest = [u8; 16];
+
+// The MD5 state after the salt, ready to take an index
+#[derive(Clone)]
+pub struct Salted {
+    context: md5::Context,
+}
+
+impl Salted {
+    pub fn new(salt: &str) -> Salted {
+        let mut context = md5::Context::new();
+        context.consume(salt.as_bytes());
+        Salted { context }
+    }
+
+    pub fn digest(&self, index: u64) -> Digest {
+        let mut digits = [0; 20];
+        let mut context = self.context.clone();
+        context.consume(decimal(index, &mut digits));
+        context.compute().0
+    }
+}
+
+// The digits of n at the end of the buffer, which fits any u64
+fn decimal(mut n: u64, buffer: &mut [u8; 20]) -> &[u8] {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buffer[start..];
+        }
+    }
+}
+
+// The i-th hex digit of the digest as it would be printed
+pub fn nibble(digest: &Digest, i: usize) -> u8 {
+    let byte = digest[i / 2];
+    if i & 1 == 0 {
+        byte >> 4
+    } else {
+        byte & 0xf
+    }
+}
+
+// Whether the hex form starts with `count` zeros
+pub fn has_zero_nibbles(digest: &Digest, count: usize) -> bool {
+    digest[..count / 2].iter().all(|&byte| byte == 0)
+        && (count & 1 == 0 || digest[count / 2] < 0x10)
+}
+
+pub fn to_hex(digest: &Digest) -> String {
+    (0..32).map(|i| hex_digit(nibble(digest, i))).collect()
+}
+
+pub fn hex_digit(nibble: u8) -> char {
+    char::from_digit(nibble as u32, 16).expect("A nibble is a hex digit")
+}
+
+const FIRST_CHUNK: u64 = 1 << 12;
+const MAX_CHUNK: u64 = 1 << 20;
+
+// The matches in increasing index order. Indices are hashed a chunk at a time on all
+// threads, chunks start small so a match near the start is found quickly.
+pub struct Mine<P> {
+    salted: Salted,
+    predicate: P,
+    next: u64,
+    chunk: u64,
+    found: VecDeque<(u64, Digest)>,
+}
+
+pub fn mine<P>(salt: &str, predicate: P) -> Mine<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    Mine {
+        salted: Salted::new(salt),
+        predicate,
+        next: 0,
+        chunk: FIRST_CHUNK,
+        found: VecDeque::new(),
+    }
+}
+
+impl<P> Iterator for Mine<P>
+where
+    P: Fn(&Digest) -> bool + Sync,
+{
+    type Item = (u64, Digest);
+
+    fn next(&mut self) -> Option<(u64, Digest)> {
+        while self.found.is_empty() {
+            let start = self.next;
+            let end = start.checked_add(self.chunk)?;
+            let (salted, predicate) = (&self.salted, &self.predicate);
+            // Collecting a parallel iterator keeps the order of the range
+            let matches = (start..end)
+                .into_par_iter()
+                .filter_map(|index| {
+                    let digest = salted.digest(index);
+                    predicate(&digest).then_some((index, digest))
+                })
+                .collect::<Vec<_>>();
+            self.found.extend(matches);
+            self.next = end;
+            self.chunk = (self.chunk * 2).min(MAX_CHUNK);
+        }
+        self.found.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test_md5_mining {
+    use super::*;
+
+    #[test]
+    fn test_decimal() {
+        let mut buffer = [0; 20];
+        assert_eq!(b"0", decimal(0, &mut buffer));
+        assert_eq!(b"3231929", decimal(3_231_929, &mut buffer));
+        assert_eq!(
+            u64::MAX.to_string().as_bytes(),
+            decimal(u64::MAX, &mut buffer)
+        );
+    }
+
+    #[test]
+    fn test_digest_matches_md5() {
+        let salted = Salted::new("abc");
+        for index in [0, 9, 10, 3_231_929, u64::MAX] {
+            let expected = md5::compute(format!("abc{index}"));
+            assert_eq!(format!("{expected:x}"), to_hex(&salted.digest(index)));
+        }
+    }
+
+    #[test]
+    fn test_nibbles() {
+        let digest = Salted::new("abc").digest(3_231_929);
+        assert!(to_hex(&digest).starts_with("000001"));
+        assert_eq!(1, nibble(&digest, 5));
+        assert!(has_zero_nibbles(&digest, 5));
+        assert!(!has_zero_nibbles(&digest, 6));
+        assert!(has_zero_nibbles(&digest, 0));
+        assert_eq!('f', hex_digit(15));
+    }
+
+    #[test]
+    fn test_mine_keeps_index_order() {
+        // About one in 256 indices matches, so this crosses several chunks
+        let salted = Salted::new("xyz");
+        let expected = (0..)
+            .filter(|&index| has_zero_nibbles(&salted.digest(index), 2))
+            .take(60)
+            .collect::<Vec<_>>();
+        let mined = mine("xyz", |digest| has_zero_nibbles(digest, 2))
+            .map(|(index, _)| index)
+            .take(60)
+            .collect::<Vec<_>>();
+        assert_eq!(expected, mined);
+    }
+}
@@ -0,0 +1,223 @@
+#![allow(unused)]
+
+// A cache for recursive solutions. Recursion goes through `Memo::solve`, which hands the
+// function a Recurse to make its recursive calls with, so a closure can call itself and
+// every call is looked up first. In verbose mode a cache reports its hits and misses when
+// it is dropped.
+
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lookups = self.hits + self.misses;
+        let rate = if lookups == 0 {
+            0.0
+        } else {
+            100.0 * self.hits as f64 / lookups as f64
+        };
+        write!(
+            f,
+            "{} hits, {} misses ({rate:.1}% hit rate)",
+            self.hits, self.misses
+        )
+    }
+}
+
+pub struct Memo<K, V> {
+    // Shows up in the verbose report
+    name: &'static str,
+    cache: HashMap<K, V>,
+    stats: Stats,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new(name: &'static str) -> Memo<K, V> {
+        Memo {
+            name,
+            cache: HashMap::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    // Looks a key up without an owned copy of it, like a &str for a String key
+    pub fn get<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let value = self.cache.get(key).cloned();
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    pub fn get_or_insert_with<Q>(&mut self, key: &Q, compute: impl FnOnce() -> V) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        if let Some(value) = self.get(key) {
+            return value;
+        }
+        let value = compute();
+        self.insert(key.to_owned(), value.clone());
+        value
+    }
+
+    // Runs a recursive function from `key` with every call cached. The cache is kept, so
+    // later calls reuse what earlier ones worked out.
+    pub fn solve<Q>(&mut self, key: &Q, function: impl Fn(&mut Recurse<K, V, Q>, &Q) -> V) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        Recurse {
+            memo: self,
+            function: &function,
+        }
+        .call(key)
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K, V> Drop for Memo<K, V> {
+    fn drop(&mut self) {
+        if verbose() {
+            eprintln!(
+                "Memo {}: {}, {} entries",
+                self.name,
+                self.stats,
+                self.cache.len()
+            );
+        }
+    }
+}
+
+type Function<'a, K, V, Q> = &'a dyn Fn(&mut Recurse<K, V, Q>, &Q) -> V;
+
+// The handle a memoized function makes its recursive calls through
+pub struct Recurse<'a, K, V, Q: ?Sized> {
+    memo: &'a mut Memo<K, V>,
+    function: Function<'a, K, V, Q>,
+}
+
+impl<K, V, Q> Recurse<'_, K, V, Q>
+where
+    K: Eq + Hash + Borrow<Q>,
+    V: Clone,
+    Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+{
+    pub fn call(&mut self, key: &Q) -> V {
+        if let Some(value) = self.memo.get(key) {
+            return value;
+        }
+        let function = self.function;
+        let value = function(self, key);
+        self.memo.insert(key.to_owned(), value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod test_memo {
+    use super::*;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.solve(&n, |fib, &n| {
+            if n < 2 {
+                n
+            } else {
+                fib.call(&(n - 1)) + fib.call(&(n - 2))
+            }
+        })
+    }
+
+    #[test]
+    fn test_recursion() {
+        let mut memo = Memo::new("fibonacci");
+        assert_eq!(12_586_269_025, fibonacci(&mut memo, 50));
+        // Every number is worked out once, the second branch always finds it cached
+        assert_eq!(51, memo.len());
+        assert_eq!(
+            Stats {
+                hits: 48,
+                misses: 51
+            },
+            memo.stats()
+        );
+
+        // A second run starts from what the first one left behind
+        assert_eq!(55, fibonacci(&mut memo, 10));
+        assert_eq!(49, memo.stats().hits);
+    }
+
+    #[test]
+    fn test_borrowed_keys() {
+        // Counts the ways to build a word out of pieces
+        let pieces = ["a", "ab", "b", "ba"];
+        let mut memo = Memo::<String, usize>::new("words");
+        let ways = memo.solve("abab", |ways, word: &str| {
+            if word.is_empty() {
+                return 1;
+            }
+            pieces
+                .iter()
+                .filter_map(|piece| word.strip_prefix(piece))
+                .map(|rest| ways.call(rest))
+                .sum()
+        });
+        assert_eq!(5, ways);
+        assert_eq!(Some(5), memo.get("abab"));
+        assert_eq!(None, memo.get("abc"));
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let mut memo = Memo::<String, usize>::new("lengths");
+        assert_eq!(5, memo.get_or_insert_with("hello", || 5));
+        assert_eq!(5, memo.get_or_insert_with("hello", || unreachable!()));
+        assert_eq!(
+            "1 hits, 1 misses (50.0% hit rate)",
+            memo.stats().to_string()
+        );
+    }
+}
@@ -0,0 +1,21 @@
+pub mod bit_grid;
+pub mod cycle;
+pub mod direction;
+pub mod flood;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod interner;
+pub mod intervals;
+pub mod md5_mining;
+pub mod memo;
+pub mod number_theory;
+pub mod ocr;
+pub mod parse;
+pub mod point;
+pub mod polynomial;
+pub mod prop;
+pub mod rng;
+pub mod search;
+pub mod sparse_grid;
+pub mod union_find;
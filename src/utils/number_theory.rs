@@ -0,0 +1,178 @@
+#![allow(unused)]
+
+// Integer helpers on top of num. Everything that can overflow returns None instead of
+// wrapping, the modular arithmetic works in i128 so any i64 modulus is safe.
+
+use num::{CheckedMul, Integer};
+
+// The gcd of every value, zero for an empty slice
+pub fn gcd_all<T: Integer + Copy>(values: &[T]) -> T {
+    values.iter().fold(T::zero(), |acc, &value| acc.gcd(&value))
+}
+
+// The lcm of two values, None when it doesn't fit in T
+pub fn checked_lcm<T: Integer + CheckedMul + Copy>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+    let lcm = (a / a.gcd(&b)).checked_mul(&b)?;
+    // The sign of the inputs shouldn't leak into the result
+    Some(if lcm < T::zero() {
+        T::zero() - lcm
+    } else {
+        lcm
+    })
+}
+
+// The lcm of every value, one for an empty slice and None on overflow
+pub fn lcm_all<T: Integer + CheckedMul + Copy>(values: &[T]) -> Option<T> {
+    values
+        .iter()
+        .try_fold(T::one(), |acc, &value| checked_lcm(acc, value))
+}
+
+// Returns (g, x, y) with a*x + b*y = g, where g is the non negative gcd
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+// The x in 0..modulus with a*x = 1, None when a and the modulus share a factor
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    assert!(modulus > 0, "The modulus must be positive");
+    let (g, x, _) = extended_gcd(a as i128, modulus as i128);
+    (g == 1).then(|| x.rem_euclid(modulus as i128) as i64)
+}
+
+// base^exp in 0..modulus by repeated squaring
+pub fn mod_pow(base: i64, mut exp: u64, modulus: i64) -> i64 {
+    assert!(modulus > 0, "The modulus must be positive");
+    let modulus = modulus as i128;
+    let mut base = (base as i128).rem_euclid(modulus);
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as i64
+}
+
+// Solves x = residue (mod modulus) for every pair at once. The moduli don't need to be
+// coprime. Returns the smallest non negative x and the lcm of the moduli, every other
+// solution differs from x by a multiple of it. None when the congruences contradict
+// each other or the lcm doesn't fit in an i64.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x = 0_i128;
+    let mut lcm = 1_i128;
+    for &(residue, modulus) in congruences {
+        assert!(modulus > 0, "The modulus must be positive");
+        let (residue, modulus) = (residue as i128, modulus as i128);
+        // lcm*p + modulus*q = g, so x + lcm*k lands on the residue for the k below
+        let (g, p, _) = extended_gcd(lcm, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = modulus / g;
+        let k = (diff / g % step * p).rem_euclid(step);
+        x += lcm * k;
+        lcm *= step;
+        if lcm > i64::MAX as i128 {
+            return None;
+        }
+        x = x.rem_euclid(lcm);
+    }
+    Some((x as i64, lcm as i64))
+}
+
+#[cfg(test)]
+mod test_number_theory {
+    use super::*;
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        assert_eq!(6, gcd_all(&[12, 18, 30]));
+        assert_eq!(0, gcd_all::<u64>(&[]));
+        assert_eq!(5, gcd_all(&[0, -5]));
+
+        assert_eq!(Some(60), lcm_all(&[4, 6, 10, 15]));
+        assert_eq!(Some(1), lcm_all::<u64>(&[]));
+        assert_eq!(Some(0), lcm_all(&[3, 0, 5]));
+        assert_eq!(Some(12), checked_lcm(-4, 6));
+    }
+
+    #[test]
+    fn test_lcm_overflow() {
+        // Coprime values whose product doesn't fit
+        let values = [1_u64 << 40, 3_u64.pow(30)];
+        assert_eq!(None, lcm_all(&values));
+        assert_eq!(
+            Some((1_u128 << 40) * 3_u128.pow(30)),
+            lcm_all(&values.map(|v| v as u128))
+        );
+        assert_eq!(Some(u64::MAX), lcm_all(&[u64::MAX, u64::MAX]));
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (7, 0), (0, -7), (17, 5), (0, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(num::integer::gcd(a, b), g);
+            assert_eq!(g, a * x + b * y);
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(6, 9));
+        assert_eq!(Some(0), mod_inverse(5, 1));
+
+        let modulus = 1_000_000_007;
+        let inverse = mod_inverse(123_456_789, modulus).unwrap();
+        assert_eq!(
+            1,
+            (123_456_789_i128 * inverse as i128 % modulus as i128) as i64
+        );
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(24, mod_pow(2, 10, 1000));
+        assert_eq!(1, mod_pow(5, 0, 7));
+        assert_eq!(0, mod_pow(5, 0, 1));
+        assert_eq!(4, mod_pow(-2, 3, 12));
+        // Fermat's little theorem with a modulus close to i64::MAX
+        let prime = 9_223_372_036_854_775_783;
+        assert_eq!(1, mod_pow(12345, prime as u64 - 1, prime));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+        // Moduli sharing a factor of two
+        assert_eq!(Some((7, 12)), crt(&[(3, 4), (1, 6)]));
+        assert_eq!(None, crt(&[(0, 4), (1, 6)]));
+        // Negative residues are fine
+        assert_eq!(Some((4, 5)), crt(&[(-1, 5)]));
+        // The lcm of these doesn't fit
+        assert_eq!(None, crt(&[(0, i64::MAX), (1, i64::MAX - 1)]));
+    }
+}
@@ -0,0 +1,217 @@
+#![allow(unused)]
+
+// Reads the block letters some puzzles draw on a screen. The small font is 6 pixels
+// high with a letter every 5 columns, the large one is 10 high with a letter every 8
+// columns. Letters sit in fixed cells from the left edge, some like Y fill their cell.
+
+use std::fmt;
+
+use super::grid::Grid;
+
+const SMALL_HEIGHT: usize = 6;
+const SMALL_CELL: usize = 5;
+const LARGE_HEIGHT: usize = 10;
+const LARGE_CELL: usize = 8;
+
+const SMALL: [(char, [&str; SMALL_HEIGHT]); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[rustfmt::skip]
+const LARGE: [(char, [&str; LARGE_HEIGHT]); 15] = [
+    ('A', ["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', ["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', [".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', ["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', [".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', ["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', ["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', ["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', ["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', ["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', ["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', ["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', ["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', ["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+// A cell that doesn't hold any letter of the font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGlyph {
+    // Which cell from the left, starting at zero
+    pub index: usize,
+    // The pixels of the cell drawn with `#` and `.`
+    pub bitmap: String,
+}
+
+impl fmt::Display for UnknownGlyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Unknown glyph at letter {}:", self.index)?;
+        write!(f, "{}", self.bitmap)
+    }
+}
+
+// The pixels of one cell, cut off or padded with dark pixels to the cell width
+fn cell_bitmap(pixels: &Grid<bool>, index: usize, cell: usize) -> String {
+    (0..pixels.height())
+        .map(|y| {
+            (index * cell..(index + 1) * cell)
+                .map(|x| match pixels.get(x, y) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn glyph_bitmap(rows: &[&str], cell: usize) -> String {
+    rows.iter()
+        .map(|row| format!("{row:.<cell$}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The letters on the screen. The font is picked by the height, empty cells are skipped.
+pub fn read_grid(pixels: &Grid<bool>) -> Result<String, UnknownGlyph> {
+    let (cell, font) = match pixels.height() {
+        SMALL_HEIGHT => (
+            SMALL_CELL,
+            SMALL
+                .iter()
+                .map(|(letter, rows)| (*letter, glyph_bitmap(rows, SMALL_CELL)))
+                .collect::<Vec<_>>(),
+        ),
+        LARGE_HEIGHT => (
+            LARGE_CELL,
+            LARGE
+                .iter()
+                .map(|(letter, rows)| (*letter, glyph_bitmap(rows, LARGE_CELL)))
+                .collect::<Vec<_>>(),
+        ),
+        _ => {
+            return Err(UnknownGlyph {
+                index: 0,
+                bitmap: cell_bitmap(pixels, 0, pixels.width()),
+            })
+        }
+    };
+
+    let mut letters = String::new();
+    for index in 0..pixels.width().div_ceil(cell) {
+        let bitmap = cell_bitmap(pixels, index, cell);
+        if !bitmap.contains('#') {
+            continue;
+        }
+        match font.iter().find(|(_, glyph)| *glyph == bitmap) {
+            Some((letter, _)) => letters.push(*letter),
+            None => return Err(UnknownGlyph { index, bitmap }),
+        }
+    }
+    Ok(letters)
+}
+
+// The same from a drawing, where `#` and `█` are lit and everything else is dark. Lines
+// can be of different lengths, so trailing spaces may be trimmed.
+pub fn read_str(drawing: &str) -> Result<String, UnknownGlyph> {
+    let lines = drawing
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let pixels = Grid::from_fn(width, lines.len(), |x, y| {
+        matches!(lines[y].chars().nth(x), Some('#' | '█'))
+    });
+    read_grid(&pixels)
+}
+
+#[cfg(test)]
+mod test_ocr {
+    use super::*;
+
+    const HELLO: &str = "\
+#..#.####.#....#.....##.
+#..#.#....#....#....#..#
+####.###..#....#....#..#
+#..#.#....#....#....#..#
+#..#.#....#....#....#..#
+#..#.####.####.####..##.";
+
+    #[test]
+    fn test_small_font() {
+        assert_eq!(Ok("HELLO".to_string()), read_str(HELLO));
+        // Spaces for dark pixels, with the trailing ones trimmed
+        let spaced = HELLO.replace('.', " ");
+        let trimmed = spaced.lines().map(str::trim_end).collect::<Vec<_>>();
+        assert_eq!(Ok("HELLO".to_string()), read_str(&trimmed.join("\n")));
+    }
+
+    #[test]
+    fn test_every_small_letter() {
+        for (letter, rows) in SMALL {
+            let drawing = rows.join("\n");
+            assert_eq!(Ok(letter.to_string()), read_str(&drawing), "{drawing}");
+        }
+    }
+
+    #[test]
+    fn test_wide_letter_fills_its_cell() {
+        let drawing = (0..SMALL_HEIGHT)
+            .map(|y| format!("{}{}", SMALL[16].1[y], SMALL[0].1[y]))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Ok("YA".to_string()), read_str(&drawing));
+    }
+
+    #[test]
+    fn test_large_font() {
+        let drawing = (0..LARGE_HEIGHT)
+            .map(|y| format!("{}..{}..", LARGE[10].1[y], LARGE[13].1[y]))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Ok("NX".to_string()), read_str(&drawing));
+        for (letter, rows) in LARGE {
+            assert_eq!(Ok(letter.to_string()), read_str(&rows.join("\n")));
+        }
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let drawing = HELLO.replace(
+            "#..#.#....#....#....#..#\n####",
+            "#..#.#....#....#....#..#\n#.##",
+        );
+        let unknown = read_str(&drawing).unwrap_err();
+        assert_eq!(0, unknown.index);
+        assert_eq!("#..#.\n#..#.\n#.##.\n#..#.\n#..#.\n#..#.", unknown.bitmap);
+        assert!(unknown
+            .to_string()
+            .starts_with("Unknown glyph at letter 0:\n#..#."));
+
+        // Not the height of either font
+        assert!(read_str("#\n#").is_err());
+    }
+}
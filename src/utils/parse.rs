@@ -0,0 +1,353 @@
+#![allow(unused)]
+
+// Parsers for the input shapes that keep coming back. They use nom's default error so
+// they mix with the parsers in the days, `finish` turns a failure into a line and
+// column. Line breaks are matched with line_ending, so CRLF input works everywhere.
+
+use std::{fmt, str::FromStr};
+
+use nom::{
+    bytes::complete::take_till1,
+    character::complete::{
+        char, i64, line_ending, multispace0, none_of, not_line_ending, space0, space1,
+    },
+    combinator::{eof, map_opt, map_res, recognize},
+    error::{Error, ErrorKind},
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, separated_pair, terminated},
+    IResult, Parser,
+};
+
+use super::grid::Grid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // Both start at one
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+    // The rest of the line where parsing stopped
+    pub found: String,
+}
+
+impl ParseError {
+    fn at(input: &str, rest: &str, kind: ErrorKind) -> ParseError {
+        let consumed = &input[..input.len() - rest.len()];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map_or(0, |newline| newline + 1);
+        let column = consumed[line_start..].chars().count() + 1;
+        let found = rest.lines().next().unwrap_or("").to_string();
+        ParseError {
+            line,
+            column,
+            kind,
+            found,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {:?} failed at {:?}",
+            self.line, self.column, self.kind, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Runs the parser on the whole input. Trailing whitespace and newlines are skipped,
+// anything else that is left over is an error.
+pub fn finish<'a, O>(
+    input: &'a str,
+    parser: impl Parser<&'a str, O, Error<&'a str>>,
+) -> Result<O, ParseError> {
+    match terminated(parser, pair(multispace0, eof))(input) {
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+            Err(ParseError::at(input, error.input, error.code))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::at(input, "", ErrorKind::Eof)),
+    }
+}
+
+// Items on one line separated by spaces or tabs, extra spaces around them are skipped
+pub fn list<'a, O>(
+    item: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    delimited(space0, separated_list1(space1, item), space0)
+}
+
+pub fn signed_numbers(input: &str) -> IResult<&str, Vec<i64>> {
+    list(i64)(input)
+}
+
+// One item per line
+pub fn lines<'a, O>(
+    item: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(line_ending, item)
+}
+
+// The end of a line followed by one or more empty lines
+pub fn blank_line(input: &str) -> IResult<&str, &str> {
+    recognize(pair(line_ending, many1(pair(space0, line_ending))))(input)
+}
+
+// Groups of lines with blank lines in between
+pub fn blocks<'a, O>(
+    block: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(blank_line, block)
+}
+
+// `label: value`, returning both
+pub fn labelled<'a, L, O>(
+    label: impl Parser<&'a str, L, Error<&'a str>>,
+    value: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (L, O)> {
+    separated_pair(label, pair(char(':'), space0), value)
+}
+
+// A line like `Time:      7  15   30`
+pub fn labelled_numbers(input: &str) -> IResult<&str, (&str, Vec<i64>)> {
+    labelled(take_till1(|c| c == ':' || c == '\n'), signed_numbers)(input)
+}
+
+// A rectangle of characters, `cell` rejects the ones that don't belong in the grid. A
+// row that is shorter or longer than the first one fails where that row ends.
+pub fn grid<'a, T>(
+    mut cell: impl FnMut(char) -> Option<T>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Grid<T>> {
+    move |input| {
+        let mut width = None;
+        let row = |input: &'a str| -> IResult<&'a str, Vec<T>> {
+            let (rest, row) = many1(map_opt(none_of("\r\n"), &mut cell))(input)?;
+            if *width.get_or_insert(row.len()) != row.len() {
+                return Err(nom::Err::Failure(Error::new(rest, ErrorKind::Verify)));
+            }
+            Ok((rest, row))
+        };
+        let (rest, rows) = separated_list1(line_ending, row)(input)?;
+        let grid = Grid::from_rows(rows).expect("Every row has the same width");
+        Ok((rest, grid))
+    }
+}
+
+pub fn char_grid(input: &str) -> IResult<&str, Grid<char>> {
+    grid(Some)(input)
+}
+
+// The rest of the line read with FromStr, which lets types that derive FromPattern take
+// part in a nom parser
+pub fn parsed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(not_line_ending, str::parse)(input)
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::*;
+    use nom::{bytes::complete::tag, character::complete::u32, sequence::preceded};
+    use pattern_derive::FromPattern;
+
+    #[test]
+    fn test_signed_numbers() {
+        assert_eq!(
+            Ok(vec![vec![0, -3, 6], vec![10, 13]]),
+            finish("0 -3  6\n 10\t13\n", lines(signed_numbers))
+        );
+        assert_eq!(
+            Ok(vec![vec![1, 2], vec![3]]),
+            finish("1 2\r\n3\r\n\r\n", lines(signed_numbers))
+        );
+    }
+
+    #[test]
+    fn test_located_errors() {
+        let error = finish("1 2\n3 x 4\n", lines(signed_numbers)).unwrap_err();
+        assert_eq!((2, 3), (error.line, error.column));
+        assert_eq!("line 2, column 3: Eof failed at \"x 4\"", error.to_string());
+
+        let error = finish("", signed_numbers).unwrap_err();
+        assert_eq!((1, 1), (error.line, error.column));
+    }
+
+    #[test]
+    fn test_blocks() {
+        // The numbers of a block start on the line after its label
+        let block = labelled(
+            take_till1(|c| c == ':'),
+            preceded(line_ending, lines(signed_numbers)),
+        );
+        let input = "a:\n1 2\n3\n\n\nb:\n4\r\n\r\nc:\n5 6\n";
+        assert_eq!(
+            Ok(vec![
+                ("a", vec![vec![1, 2], vec![3]]),
+                ("b", vec![vec![4]]),
+                ("c", vec![vec![5, 6]]),
+            ]),
+            finish(input, blocks(block))
+        );
+    }
+
+    #[test]
+    fn test_labelled() {
+        let input = "Time:      7  15   30\nDistance:  9  40  200\n";
+        assert_eq!(
+            Ok(vec![
+                ("Time", vec![7, 15, 30]),
+                ("Distance", vec![9, 40, 200])
+            ]),
+            finish(input, lines(labelled_numbers))
+        );
+
+        let card = labelled(
+            preceded(pair(tag("Card"), space1), u32),
+            separated_pair(list(u32), char('|'), list(u32)),
+        );
+        assert_eq!(
+            Ok((3, (vec![1, 21], vec![69, 82]))),
+            finish("Card   3:  1 21 | 69 82", card)
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let map = finish("#.#\r\n..#\n", char_grid).unwrap();
+        assert_eq!((3, 2), (map.width(), map.height()));
+        assert_eq!('#', map[(2, 1)]);
+
+        let digits = finish("12\n34", grid(|c| c.to_digit(10))).unwrap();
+        assert_eq!(4, digits[(1, 1)]);
+
+        let ragged = finish("#.#\n.#\n", char_grid).unwrap_err();
+        assert_eq!(ErrorKind::Verify, ragged.kind);
+        let bad_cell = finish("12\n3x", grid(|c| c.to_digit(10))).unwrap_err();
+        assert_eq!((2, 2), (bad_cell.line, bad_cell.column));
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    #[pattern("Game {id}: {draws:; }")]
+    struct Game {
+        id: u32,
+        draws: Vec<Draw>,
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    #[pattern("{0:, }")]
+    struct Draw(Vec<Cubes>);
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    #[pattern("{count} {colour}")]
+    struct Cubes {
+        count: u32,
+        colour: Colour,
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    enum Colour {
+        #[pattern("red")]
+        Red,
+        #[pattern("green")]
+        Green,
+        #[pattern("blue")]
+        Blue,
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    #[pattern("{name}-{sector}[{checksum}]")]
+    struct Room {
+        name: String,
+        sector: u32,
+        checksum: String,
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    enum Instruction {
+        #[pattern("value {value} goes to bot {bot}")]
+        Value { value: u32, bot: u32 },
+        #[pattern("bot {0} gives low to {1} and high to {2}")]
+        Gives(u32, Target, Target),
+    }
+
+    #[derive(Debug, PartialEq, FromPattern)]
+    enum Target {
+        #[pattern("bot {0}")]
+        Bot(u32),
+        #[pattern("output {0}")]
+        Output(u32),
+    }
+
+    #[test]
+    fn test_derive_struct() {
+        let game = "Game 3: 8 green, 6 blue; 5 red".parse::<Game>();
+        let cubes = |count, colour| Cubes { count, colour };
+        assert_eq!(
+            Ok(Game {
+                id: 3,
+                draws: vec![
+                    Draw(vec![cubes(8, Colour::Green), cubes(6, Colour::Blue)]),
+                    Draw(vec![cubes(5, Colour::Red)]),
+                ],
+            }),
+            game
+        );
+
+        // The name takes every dash but the last one
+        assert_eq!(
+            Ok(Room {
+                name: "aaaaa-bbb-z-y-x".to_string(),
+                sector: 123,
+                checksum: "abxyz".to_string(),
+            }),
+            "aaaaa-bbb-z-y-x-123[abxyz]".parse()
+        );
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        assert_eq!(
+            Ok(Instruction::Value { value: 5, bot: 2 }),
+            "value 5 goes to bot 2".parse()
+        );
+        assert_eq!(
+            Ok(Instruction::Gives(2, Target::Bot(1), Target::Output(0))),
+            "bot 2 gives low to bot 1 and high to output 0".parse()
+        );
+    }
+
+    #[test]
+    fn test_derive_errors() {
+        assert_eq!(
+            Err("expected \"Game \" at the start of \"Gam 1: 1 red\"".to_string()),
+            "Gam 1: 1 red".parse::<Game>()
+        );
+        assert_eq!(
+            Err("`count` can't be \"x\": invalid digit found in string".to_string()),
+            "x red".parse::<Cubes>()
+        );
+        assert_eq!(
+            Err("expected \"]\" at the end of \"abc\"".to_string()),
+            "name-1[abc".parse::<Room>()
+        );
+        let error = "bot 1 gives low to bin 2 and high to bot 3"
+            .parse::<Instruction>()
+            .unwrap_err();
+        assert!(error.contains("\"bin 2\" is not a Target"), "{error}");
+    }
+
+    #[test]
+    fn test_parsed() {
+        let input = "bot 0 gives low to output 2 and high to output 0\nvalue 2 goes to bot 2\n";
+        assert_eq!(
+            Ok(vec![
+                Instruction::Gives(0, Target::Output(2), Target::Output(0)),
+                Instruction::Value { value: 2, bot: 2 },
+            ]),
+            finish(input, lines(parsed))
+        );
+        assert!(finish("value x goes to bot 2", lines(parsed::<Instruction>)).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+#![allow(unused)]
+
+use std::{
+    cmp::Ordering,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use num::Zero;
+
+use super::direction::Direction;
+
+// Points double as vectors, so a position plus a unit vector times a distance is just
+// `pos + dir * distance`. The derived ordering compares x before y (before z), use
+// `reading_order` for top to bottom, left to right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+// Works for unsigned coordinates too, where a - b could underflow
+fn abs_diff<T: PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+macro_rules! point_ops {
+    ($point:ident { $($axis:ident),+ }) => {
+        impl<T> $point<T> {
+            pub const fn new($($axis: T),+) -> $point<T> {
+                $point { $($axis),+ }
+            }
+
+            pub fn origin() -> $point<T>
+            where
+                T: Zero,
+            {
+                $point { $($axis: T::zero()),+ }
+            }
+
+            pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> $point<U> {
+                $point { $($axis: f(self.$axis)),+ }
+            }
+
+            // Converts every coordinate, None if one of them doesn't fit, for example a
+            // negative coordinate into usize
+            pub fn try_cast<U: TryFrom<T>>(self) -> Option<$point<U>> {
+                Some($point { $($axis: U::try_from(self.$axis).ok()?),+ })
+            }
+
+            pub fn manhattan(self, other: $point<T>) -> T
+            where
+                T: Copy + PartialOrd + Zero + Sub<Output = T>,
+            {
+                T::zero() $(+ abs_diff(self.$axis, other.$axis))+
+            }
+
+            // The distance when diagonal steps are allowed too
+            pub fn chebyshev(self, other: $point<T>) -> T
+            where
+                T: Copy + PartialOrd + Zero + Sub<Output = T>,
+            {
+                let mut distance = T::zero();
+                $(distance = max(distance, abs_diff(self.$axis, other.$axis));)+
+                distance
+            }
+        }
+
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = $point<T>;
+
+            fn add(self, other: $point<T>) -> $point<T> {
+                $point { $($axis: self.$axis + other.$axis),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = $point<T>;
+
+            fn sub(self, other: $point<T>) -> $point<T> {
+                $point { $($axis: self.$axis - other.$axis),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+            type Output = $point<T>;
+
+            fn mul(self, scalar: T) -> $point<T> {
+                $point { $($axis: self.$axis * scalar),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = $point<T>;
+
+            fn neg(self) -> $point<T> {
+                $point { $($axis: -self.$axis),+ }
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> AddAssign for $point<T> {
+            fn add_assign(&mut self, other: $point<T>) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> SubAssign for $point<T> {
+            fn sub_assign(&mut self, other: $point<T>) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+point_ops!(Point2 { x, y });
+point_ops!(Point3 { x, y, z });
+
+impl<T> Point2<T> {
+    // Top to bottom, then left to right
+    pub fn reading_order(&self, other: &Point2<T>) -> Ordering
+    where
+        T: Ord,
+    {
+        (&self.y, &self.x).cmp(&(&other.y, &other.x))
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Point2<T> {
+        Point2 { x, y }
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+    fn from(point: Point2<T>) -> (T, T) {
+        (point.x, point.y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Point3<T> {
+        Point3 { x, y, z }
+    }
+}
+
+impl From<Direction> for Point2<i64> {
+    fn from(direction: Direction) -> Point2<i64> {
+        direction.unit_vector().into()
+    }
+}
+
+#[cfg(test)]
+mod test_point {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point2::new(1, -2);
+        let b = Point2::new(3, 4);
+        assert_eq!(Point2::new(4, 2), a + b);
+        assert_eq!(Point2::new(-2, -6), a - b);
+        assert_eq!(Point2::new(3, -6), a * 3);
+        assert_eq!(Point2::new(-1, 2), -a);
+
+        let mut c = Point3::new(1, 2, 3);
+        c += Point3::new(1, 1, 1);
+        c -= Point3::new(0, 0, 5);
+        assert_eq!(Point3::new(2, 3, -1), c);
+
+        let walked = Point2::origin() + Point2::from(Direction::North) * 5;
+        assert_eq!(Point2::new(0, -5), walked);
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point2::new(1_i64, -2);
+        let b = Point2::new(-3, 4);
+        assert_eq!(10, a.manhattan(b));
+        assert_eq!(6, a.chebyshev(b));
+        assert_eq!(a.manhattan(b), b.manhattan(a));
+
+        // Mixing up the axes would give 5 here
+        assert_eq!(7, Point2::new(2_u32, 5).manhattan(Point2::new(0, 0)));
+        assert_eq!(4, Point2::new(7_usize, 3).manhattan(Point2::new(5, 5)));
+        assert_eq!(9, Point3::new(1, 2, 3).manhattan(Point3::new(4, 0, 7)));
+        assert_eq!(4, Point3::new(1, 2, 3).chebyshev(Point3::new(4, 0, 7)));
+    }
+
+    #[test]
+    fn test_try_cast() {
+        assert_eq!(
+            Some(Point2::new(3_usize, 4)),
+            Point2::new(3_i64, 4).try_cast()
+        );
+        assert_eq!(None, Point2::new(3_i64, -1).try_cast::<usize>());
+        assert_eq!(None, Point2::new(300_u32, 1).try_cast::<u8>());
+        assert_eq!(
+            Some(Point3::new(1_i32, 2, 3)),
+            Point3::new(1_u64, 2, 3).try_cast()
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut points = vec![Point2::new(2, 0), Point2::new(0, 1), Point2::new(1, 0)];
+        points.sort();
+        assert_eq!(
+            vec![Point2::new(0, 1), Point2::new(1, 0), Point2::new(2, 0)],
+            points
+        );
+        points.sort_by(Point2::reading_order);
+        assert_eq!(
+            vec![Point2::new(1, 0), Point2::new(2, 0), Point2::new(0, 1)],
+            points
+        );
+    }
+}
@@ -0,0 +1,184 @@
+#![allow(unused)]
+
+// Polynomials through evenly spaced values. The difference table is built once and its
+// leading diagonal kept, which is the Newton forward form: f(x) = sum of Δʲf(0) * C(x, j).
+// That evaluates anywhere, before the first value or past the last, and at fractions too,
+// all in exact rationals.
+
+use std::fmt;
+
+use num::{bigint::BigInt, rational::BigRational, One, Zero};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotPolynomial {
+    pub length: usize,
+}
+
+impl fmt::Display for NotPolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} values never reach a row of zero differences",
+            self.length
+        )
+    }
+}
+
+// Each row holds the differences of the one before, the first row is the values
+pub fn difference_table(values: &[BigRational]) -> Vec<Vec<BigRational>> {
+    let mut table = vec![values.to_vec()];
+    while let Some(row) = table.last().filter(|row| row.len() > 1) {
+        let next = row.windows(2).map(|pair| &pair[1] - &pair[0]).collect();
+        table.push(next);
+    }
+    table
+}
+
+fn rationals<T: Clone + Into<BigInt>>(values: &[T]) -> Vec<BigRational> {
+    values
+        .iter()
+        .map(|value| BigRational::from_integer(value.clone().into()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    // Δʲf(0) for j up to the degree, empty for the zero polynomial
+    leading: Vec<BigRational>,
+}
+
+impl Polynomial {
+    // The values are f(0), f(1), ... They are a polynomial only if the table reaches a
+    // row of zeros, otherwise any next value would fit and nothing can be extrapolated.
+    pub fn fit<T: Clone + Into<BigInt>>(values: &[T]) -> Result<Polynomial, NotPolynomial> {
+        let values = rationals(values);
+        let table = difference_table(&values);
+        let degree = table
+            .iter()
+            .position(|row| !row.is_empty() && row.iter().all(Zero::is_zero))
+            .ok_or(NotPolynomial {
+                length: values.len(),
+            })?;
+        Ok(Polynomial {
+            leading: table[..degree].iter().map(|row| row[0].clone()).collect(),
+        })
+    }
+
+    // The lowest degree polynomial through every value, as if the last row of the table
+    // were followed by zeros. The same as `fit` when that works, and a guess when it
+    // doesn't, like a constant for a single value.
+    pub fn interpolate<T: Clone + Into<BigInt>>(values: &[T]) -> Polynomial {
+        let table = difference_table(&rationals(values));
+        let mut leading = table
+            .iter()
+            .filter_map(|row| row.first().cloned())
+            .collect::<Vec<_>>();
+        while leading.last().is_some_and(Zero::is_zero) {
+            leading.pop();
+        }
+        Polynomial { leading }
+    }
+
+    // None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.leading.len().checked_sub(1)
+    }
+
+    pub fn at(&self, x: &BigRational) -> BigRational {
+        let mut binomial = BigRational::one();
+        let mut sum = BigRational::zero();
+        for (j, difference) in self.leading.iter().enumerate() {
+            if j > 0 {
+                // C(x, j) = C(x, j - 1) * (x - j + 1) / j
+                let j = BigRational::from_integer(j.into());
+                binomial = binomial * (x - &j + BigRational::one()) / j;
+            }
+            sum += difference * &binomial;
+        }
+        sum
+    }
+
+    // The value `index` steps from the first one, negative indices go backwards
+    pub fn at_index(&self, index: i64) -> BigRational {
+        self.at(&BigRational::from_integer(index.into()))
+    }
+}
+
+#[cfg(test)]
+mod test_polynomial {
+    use super::*;
+
+    fn rational(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn test_difference_table() {
+        let values = [1, 4, 9].map(|v| rational(v, 1));
+        let table = difference_table(&values);
+        assert_eq!(
+            vec![vec![1, 4, 9], vec![3, 5], vec![2]]
+                .into_iter()
+                .map(|row| row.into_iter().map(BigInt::from).collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            table
+                .iter()
+                .map(|row| row.iter().map(|v| v.to_integer()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(Some(1), Polynomial::fit(&[0, 3, 6, 9]).unwrap().degree());
+        assert_eq!(Some(2), Polynomial::fit(&[1, 3, 6, 10]).unwrap().degree());
+        assert_eq!(Some(0), Polynomial::fit(&[7, 7]).unwrap().degree());
+        assert_eq!(None, Polynomial::fit(&[0, 0, 0]).unwrap().degree());
+    }
+
+    #[test]
+    fn test_extrapolate_both_ways() {
+        // 10, 13, 16, 21, 30, 45 is 10 + 3x + x(x - 1)(x - 2)/3
+        let polynomial = Polynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(rational(68, 1), polynomial.at_index(6));
+        assert_eq!(rational(5, 1), polynomial.at_index(-1));
+        assert_eq!(rational(-4, 1), polynomial.at_index(-2));
+        assert_eq!(rational(323_710, 1), polynomial.at_index(100));
+    }
+
+    #[test]
+    fn test_fractional_point() {
+        // The triangular numbers are x(x + 1)/2
+        let polynomial = Polynomial::fit(&[0, 1, 3, 6]).unwrap();
+        assert_eq!(rational(3, 8), polynomial.at(&rational(1, 2)));
+    }
+
+    #[test]
+    fn test_not_polynomial() {
+        assert_eq!(
+            Err(NotPolynomial { length: 5 }),
+            Polynomial::fit(&[1, 2, 4, 8, 16])
+        );
+        // One value can't show that its differences are zero
+        assert_eq!(Err(NotPolynomial { length: 1 }), Polynomial::fit(&[5]));
+        assert_eq!(
+            Err(NotPolynomial { length: 0 }),
+            Polynomial::fit::<i64>(&[])
+        );
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let fitted = Polynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(fitted, Polynomial::interpolate(&[10, 13, 16, 21, 30, 45]));
+
+        // Powers of two look like the sum of C(x, j) for j up to 4
+        let powers = Polynomial::interpolate(&[1, 2, 4, 8, 16]);
+        assert_eq!(Some(4), powers.degree());
+        assert_eq!(rational(31, 1), powers.at_index(5));
+        assert_eq!(rational(1, 1), powers.at_index(-1));
+
+        assert_eq!(rational(5, 1), Polynomial::interpolate(&[5]).at_index(3));
+        assert_eq!(None, Polynomial::interpolate::<i64>(&[]).degree());
+    }
+}
@@ -0,0 +1,297 @@
+#![allow(unused)]
+
+// A tiny property testing harness. Inputs are generated from a seeded Rng so every run
+// is reproducible, and a failing input is shrunk before it is reported so it can be
+// pasted straight into a unit test.
+//
+// The seed and the number of cases can be changed with the PROP_SEED and PROP_CASES
+// environment variables.
+
+use std::{
+    cell::Cell,
+    env,
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+use super::rng::Rng;
+
+const DEFAULT_SEED: u64 = 0x5EED;
+const DEFAULT_CASES: u32 = 200;
+const MAX_SHRINK_STEPS: u32 = 1000;
+
+// Values that know how to produce "smaller" versions of themselves
+pub trait Shrink: Sized {
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! shrink_integer {
+    ($($t:ty),*) => {$(
+        impl Shrink for $t {
+            // Towards zero: zero itself, half the value, and one step closer
+            #[allow(unused_comparisons)]
+            fn shrink(&self) -> Vec<$t> {
+                let value = *self;
+                if value == 0 {
+                    return Vec::new();
+                }
+                let closer = if value < 0 { value + 1 } else { value - 1 };
+                let mut candidates = vec![0, value / 2, closer];
+                candidates.dedup();
+                candidates.retain(|&c| c != value);
+                candidates
+            }
+        }
+    )*};
+}
+
+shrink_integer!(i32, i64, u8, u32, u64, usize);
+
+impl Shrink for char {
+    fn shrink(&self) -> Vec<char> {
+        if *self == 'a' {
+            Vec::new()
+        } else {
+            vec!['a']
+        }
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    // Drops big chunks first, then single elements, then shrinks elements in place
+    fn shrink(&self) -> Vec<Vec<T>> {
+        let mut candidates = Vec::new();
+        let len = self.len();
+
+        let mut chunk = len / 2;
+        while chunk > 0 {
+            for start in (0..len).step_by(chunk) {
+                let mut smaller = self.clone();
+                smaller.drain(start..(start + chunk).min(len));
+                candidates.push(smaller);
+            }
+            chunk /= 2;
+        }
+
+        for (idx, item) in self.iter().enumerate() {
+            for smaller_item in item.shrink() {
+                let mut smaller = self.clone();
+                smaller[idx] = smaller_item;
+                candidates.push(smaller);
+            }
+        }
+        candidates
+    }
+}
+
+impl Shrink for String {
+    fn shrink(&self) -> Vec<String> {
+        self.chars()
+            .collect::<Vec<char>>()
+            .shrink()
+            .into_iter()
+            .map(String::from_iter)
+            .collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<(A, B)> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone, C: Shrink + Clone> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<(A, B, C)> {
+        let (a, b, c) = self;
+        let firsts = a.shrink().into_iter().map(|a| (a, b.clone(), c.clone()));
+        let seconds = b.shrink().into_iter().map(|b| (a.clone(), b, c.clone()));
+        let thirds = c.shrink().into_iter().map(|c| (a.clone(), b.clone(), c));
+        firsts.chain(seconds).chain(thirds).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    pub seed: u64,
+    pub cases: u32,
+}
+
+impl Config {
+    pub fn from_env() -> Config {
+        let read = |name: &str| env::var(name).ok().and_then(|v| v.parse().ok());
+        Config {
+            seed: read("PROP_SEED").unwrap_or(DEFAULT_SEED),
+            cases: read("PROP_CASES").map_or(DEFAULT_CASES, |cases| cases as u32),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Failure<T> {
+    pub case: u32,
+    pub original: T,
+    pub minimal: T,
+}
+
+thread_local! {
+    static QUIET_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+// Keeps the hundreds of expected panics while shrinking out of the test output. The
+// hook is global, so it is installed once and only silenced on the checking thread.
+fn install_quiet_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET_PANICS.with(Cell::get) {
+                default_hook(info)
+            }
+        }));
+    });
+}
+
+// Runs `f` without printing anything if it panics, and returns the panic message
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    install_quiet_hook();
+    QUIET_PANICS.with(|quiet| quiet.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    QUIET_PANICS.with(|quiet| quiet.set(false));
+
+    result.map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string())
+    })
+}
+
+// A panic inside the property counts as a failure, just like returning false
+fn holds<T>(property: &impl Fn(&T) -> bool, value: &T) -> bool {
+    catch_panic(|| property(value)).unwrap_or(false)
+}
+
+// Shrinks a value for as long as it keeps failing
+pub fn minimize<T: Shrink>(mut value: T, fails: impl Fn(&T) -> bool) -> T {
+    for _ in 0..MAX_SHRINK_STEPS {
+        match value.shrink().into_iter().find(&fails) {
+            Some(smaller) => value = smaller,
+            None => break,
+        }
+    }
+    value
+}
+
+// Runs the property on `config.cases` generated inputs and returns the first failure,
+// shrunk as far as possible
+pub fn find_failure<T: Shrink + Clone>(
+    config: Config,
+    generate: impl Fn(&mut Rng) -> T,
+    property: impl Fn(&T) -> bool,
+) -> Option<Failure<T>> {
+    let mut rng = Rng::new(config.seed);
+
+    (0..config.cases).find_map(|case| {
+        let value = generate(&mut rng);
+        (!holds(&property, &value)).then(|| Failure {
+            case,
+            original: value.clone(),
+            minimal: minimize(value, |c| !holds(&property, c)),
+        })
+    })
+}
+
+// Checks a property with the configuration from the environment and panics with the
+// minimal failing input if it doesn't hold
+pub fn check<T: Shrink + Clone + Debug>(
+    name: &str,
+    generate: impl Fn(&mut Rng) -> T,
+    property: impl Fn(&T) -> bool,
+) {
+    let config = Config::from_env();
+    if let Some(failure) = find_failure(config, generate, property) {
+        panic!(
+            "Property '{name}' failed on case {} (PROP_SEED={}).\nOriginal input: {:?}\nMinimal input:  {:?}",
+            failure.case, config.seed, failure.original, failure.minimal
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_prop {
+    use super::*;
+
+    const CONFIG: Config = Config {
+        seed: 1,
+        cases: 100,
+    };
+
+    #[test]
+    fn test_shrink_integers() {
+        assert_eq!(vec![0, 5, 9], 10_i64.shrink());
+        assert_eq!(vec![0, -1], (-2_i64).shrink());
+        assert_eq!(Vec::<u32>::new(), 0_u32.shrink());
+    }
+
+    #[test]
+    fn test_shrink_vec() {
+        let candidates = vec![3_u32, 1].shrink();
+        assert!(candidates.contains(&vec![3]));
+        assert!(candidates.contains(&vec![1]));
+        assert!(candidates.contains(&vec![0, 1]));
+    }
+
+    #[test]
+    fn test_passing_property() {
+        let failure = find_failure(CONFIG, |rng| rng.range(-100..100), |&n| n * 2 % 2 == 0);
+        assert_eq!(None, failure);
+    }
+
+    #[test]
+    fn test_shrinks_to_minimal_input() {
+        let failure = find_failure(
+            CONFIG,
+            |rng| {
+                (0..rng.index(20))
+                    .map(|_| rng.range(0..100))
+                    .collect::<Vec<i64>>()
+            },
+            |numbers| numbers.iter().all(|&n| n < 50),
+        )
+        .expect("some generated vector has a large number");
+
+        assert_eq!(vec![50], failure.minimal);
+    }
+
+    #[test]
+    fn test_panic_counts_as_failure() {
+        let failure = find_failure(
+            CONFIG,
+            |rng| rng.below(10) as u32,
+            |&n| 10 / (n as i32 - 3) < 100,
+        )
+        .expect("division by zero is found");
+
+        assert_eq!(3, failure.minimal);
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(Ok(3), catch_panic(|| 1 + 2));
+        assert_eq!(
+            Err("boom 7".to_string()),
+            catch_panic(|| panic!("boom {}", 7))
+        );
+    }
+
+    #[test]
+    fn test_same_seed_same_failure() {
+        let run = || find_failure(CONFIG, |rng| rng.range(0..1000), |&n| n < 900);
+        assert_eq!(run(), run());
+    }
+}
@@ -0,0 +1,314 @@
+#![allow(unused)]
+
+// Searches over any state type. The graph is never built up front, a successor closure
+// produces the neighbours of a state when the search gets there.
+
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use num::Zero;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S, C> {
+    pub cost: C,
+    // From the start to the goal, both included
+    pub path: Vec<S>,
+}
+
+// Every state the search has seen, stored once and referred to by index so the state
+// type doesn't need to be Ord or Copy
+struct Visited<S, C> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+    // Best known cost and the state it was reached from
+    best: Vec<(C, Option<usize>)>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Visited<S, C> {
+    fn new(start: S, zero: C) -> Visited<S, C> {
+        Visited {
+            states: vec![start.clone()],
+            index: HashMap::from([(start, 0)]),
+            best: vec![(zero, None)],
+        }
+    }
+
+    // Records a cheaper way to reach a state, None if it was already reached as cheaply
+    fn improve(&mut self, state: S, cost: C, parent: usize) -> Option<usize>
+    where
+        C: PartialOrd,
+    {
+        match self.index.entry(state) {
+            Entry::Occupied(entry) => {
+                let idx = *entry.get();
+                (cost < self.best[idx].0).then(|| {
+                    self.best[idx] = (cost, Some(parent));
+                    idx
+                })
+            }
+            Entry::Vacant(entry) => {
+                let idx = self.states.len();
+                self.states.push(entry.key().clone());
+                self.best.push((cost, Some(parent)));
+                entry.insert(idx);
+                Some(idx)
+            }
+        }
+    }
+
+    fn path_to(&self, mut idx: usize) -> Found<S, C> {
+        let cost = self.best[idx].0;
+        let mut path = vec![self.states[idx].clone()];
+        while let Some(parent) = self.best[idx].1 {
+            path.push(self.states[parent].clone());
+            idx = parent;
+        }
+        path.reverse();
+        Found { cost, path }
+    }
+
+    fn distances(self) -> HashMap<S, C> {
+        self.states
+            .into_iter()
+            .zip(self.best)
+            .map(|(state, (cost, _))| (state, cost))
+            .collect()
+    }
+}
+
+// Breadth first search where every step costs one. Without a goal it runs until every
+// reachable state is visited.
+fn bfs_visit<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> (Visited<S, usize>, Option<usize>)
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut visited = Visited::new(start, 0);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(idx) = queue.pop_front() {
+        if is_goal(&visited.states[idx]) {
+            return (visited, Some(idx));
+        }
+        let steps = visited.best[idx].0 + 1;
+        for next in successors(&visited.states[idx]) {
+            if !visited.index.contains_key(&next) {
+                let next_idx = visited.improve(next, steps, idx).expect("A new state");
+                queue.push_back(next_idx);
+            }
+        }
+    }
+    (visited, None)
+}
+
+pub fn bfs<S, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Found<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let (visited, goal) = bfs_visit(start, successors, is_goal);
+    goal.map(|idx| visited.path_to(idx))
+}
+
+// The number of steps to every reachable state
+pub fn bfs_distances<S, I>(start: S, successors: impl FnMut(&S) -> I) -> HashMap<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    bfs_visit(start, successors, |_| false).0.distances()
+}
+
+// A* with a heuristic that must never overestimate the remaining cost. A heuristic of
+// zero makes it Dijkstra.
+fn astar_visit<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> (Visited<S, C>, Option<usize>)
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let estimate = heuristic(&start);
+    let mut visited = Visited::new(start, C::zero());
+    let mut queue = BinaryHeap::from([Reverse((estimate, C::zero(), 0))]);
+
+    while let Some(Reverse((_, cost, idx))) = queue.pop() {
+        // A cheaper way to this state was queued after this one
+        if cost > visited.best[idx].0 {
+            continue;
+        }
+        if is_goal(&visited.states[idx]) {
+            return (visited, Some(idx));
+        }
+        for (next, step) in successors(&visited.states[idx]) {
+            let next_cost = cost + step;
+            let estimate = next_cost + heuristic(&next);
+            if let Some(next_idx) = visited.improve(next, next_cost, idx) {
+                queue.push(Reverse((estimate, next_cost, next_idx)));
+            }
+        }
+    }
+    (visited, None)
+}
+
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Found<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::zero(), is_goal)
+}
+
+// The cheapest cost to every reachable state
+pub fn dijkstra_distances<S, C, I>(start: S, successors: impl FnMut(&S) -> I) -> HashMap<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar_visit(start, successors, |_| C::zero(), |_| false)
+        .0
+        .distances()
+}
+
+pub fn astar<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    heuristic: impl FnMut(&S) -> C,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Found<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Zero + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let (visited, goal) = astar_visit(start, successors, heuristic, is_goal);
+    goal.map(|idx| visited.path_to(idx))
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+    use crate::utils::{grid::Grid, point::Point2};
+
+    const MAZE: &str = "\
+S.#.....
+.##.###.
+....#...
+.####.#.
+......#G";
+
+    fn open_neighbours(maze: &Grid<char>, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        maze.neighbours4(x, y)
+            .filter(|&pos| maze[pos] != '#')
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = Grid::parse(MAZE, |c| c).unwrap();
+        let start = maze.position(|&c| c == 'S').unwrap();
+        let found = bfs(
+            start,
+            |&pos| open_neighbours(&maze, pos),
+            |&pos| maze[pos] == 'G',
+        )
+        .expect("The goal is reachable");
+
+        assert_eq!(15, found.cost);
+        assert_eq!(16, found.path.len());
+        assert_eq!(Some(&(0, 0)), found.path.first());
+        assert_eq!(Some(&(7, 4)), found.path.last());
+        assert!(found
+            .path
+            .windows(2)
+            .all(|step| open_neighbours(&maze, step[0]).contains(&step[1])));
+
+        assert_eq!(
+            None,
+            bfs(start, |&pos| open_neighbours(&maze, pos), |_| false)
+        );
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let maze = Grid::parse(MAZE, |c| c).unwrap();
+        let distances = bfs_distances((0, 0), |&pos| open_neighbours(&maze, pos));
+        assert_eq!(Some(&0), distances.get(&(0, 0)));
+        assert_eq!(Some(&15), distances.get(&(7, 4)));
+        assert_eq!(None, distances.get(&(2, 0)));
+        assert_eq!(
+            maze.iter().filter(|(_, &c)| c != '#').count(),
+            distances.len()
+        );
+    }
+
+    // A graph where the direct edge is more expensive than going around
+    fn weighted(node: &char) -> Vec<(char, u32)> {
+        match node {
+            'a' => vec![('b', 7), ('c', 2)],
+            'c' => vec![('d', 2)],
+            'd' => vec![('b', 1), ('e', 10)],
+            'b' => vec![('e', 3)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let found = dijkstra('a', weighted, |&n| n == 'e').unwrap();
+        assert_eq!(8, found.cost);
+        assert_eq!(vec!['a', 'c', 'd', 'b', 'e'], found.path);
+
+        let distances = dijkstra_distances('a', weighted);
+        assert_eq!(Some(&5), distances.get(&'b'));
+        assert_eq!(5, distances.len());
+        assert_eq!(None, dijkstra('e', weighted, |&n| n == 'a'));
+    }
+
+    #[test]
+    fn test_astar() {
+        // An open 20x20 field, the heuristic is exact without obstacles
+        let goal = Point2::new(19_i64, 19);
+        let successors = |&pos: &Point2<i64>| {
+            [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                .into_iter()
+                .map(move |step| pos + step.into())
+                .filter(|next| (0..20).contains(&next.x) && (0..20).contains(&next.y))
+                .map(|next| (next, 1))
+        };
+        let found = astar(
+            Point2::origin(),
+            successors,
+            |pos| pos.manhattan(goal),
+            |&pos| pos == goal,
+        )
+        .unwrap();
+        assert_eq!(38, found.cost);
+        assert_eq!(39, found.path.len());
+
+        let without_heuristic = dijkstra(Point2::origin(), successors, |&pos| pos == goal);
+        assert_eq!(Some(38), without_heuristic.map(|found| found.cost));
+    }
+}
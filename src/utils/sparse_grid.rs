@@ -0,0 +1,212 @@
+#![allow(unused)]
+
+use std::collections::{hash_map, HashMap};
+
+use itertools::Itertools;
+
+use super::{grid::Grid, point::Point2};
+
+pub type Pos = Point2<i64>;
+
+// A grid on the infinite plane that only stores the cells that were set. The bounds
+// grow to cover every cell that was ever inserted, removing cells doesn't shrink them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Pos, T>,
+    bounds: Option<(Pos, Pos)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> SparseGrid<T> {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    // Returns the old value, like HashMap::insert
+    pub fn insert(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.grow(pos);
+        self.cells.insert(pos, value)
+    }
+
+    fn grow(&mut self, pos: Pos) {
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((min, max)) => (
+                Pos::new(min.x.min(pos.x), min.y.min(pos.y)),
+                Pos::new(max.x.max(pos.x), max.y.max(pos.y)),
+            ),
+        });
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    // For cells that are updated in place, like counters
+    pub fn entry(&mut self, pos: Pos) -> hash_map::Entry<'_, Pos, T> {
+        self.grow(pos);
+        self.cells.entry(pos)
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn remove(&mut self, pos: Pos) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // In no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    // The smallest and largest corner, both inclusive. None until a cell is set.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        self.bounds
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.x - min.x + 1) as usize)
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.y - min.y + 1) as usize)
+    }
+
+    // Draws the cells within the bounds, one line per row. With an origin marker the
+    // origin is always part of the picture and drawn with that character.
+    pub fn render(&self, origin: Option<char>, mut cell: impl FnMut(Option<&T>) -> char) -> String {
+        let mut bounds = self.bounds;
+        if origin.is_some() {
+            let zero = Pos::origin();
+            bounds = Some(bounds.map_or((zero, zero), |(min, max)| {
+                (
+                    Pos::new(min.x.min(0), min.y.min(0)),
+                    Pos::new(max.x.max(0), max.y.max(0)),
+                )
+            }));
+        }
+        let Some((min, max)) = bounds else {
+            return String::new();
+        };
+
+        (min.y..=max.y)
+            .map(|y| {
+                (min.x..=max.x)
+                    .map(|x| match origin {
+                        Some(marker) if (x, y) == (0, 0) => marker,
+                        _ => cell(self.get(Pos::new(x, y))),
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
+    // The bounded region as a dense grid, the top left cell of the grid is the smallest
+    // corner of the bounds. Cells that were never set are `empty`.
+    pub fn to_grid(&self, empty: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let min = self.bounds.map_or(Pos::origin(), |(min, _)| min);
+        Grid::from_fn(self.width(), self.height(), |x, y| {
+            let pos = min + Pos::new(x as i64, y as i64);
+            self.get(pos).unwrap_or(&empty).clone()
+        })
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> SparseGrid<T> {
+        let mut grid = SparseGrid::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Pos, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Pos, T)>>(&mut self, iter: I) {
+        for (pos, value) in iter {
+            self.insert(pos, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sparse_grid {
+    use super::*;
+
+    fn sample() -> SparseGrid<char> {
+        [(Pos::new(-2, 1), 'a'), (Pos::new(1, 3), 'b')]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_bounds_grow() {
+        let mut grid = sample();
+        assert_eq!(Some((Pos::new(-2, 1), Pos::new(1, 3))), grid.bounds());
+        assert_eq!((4, 3), (grid.width(), grid.height()));
+
+        grid.insert(Pos::new(5, -1), 'c');
+        assert_eq!(Some((Pos::new(-2, -1), Pos::new(5, 3))), grid.bounds());
+
+        assert_eq!(Some('c'), grid.remove(Pos::new(5, -1)));
+        assert_eq!(Some((Pos::new(-2, -1), Pos::new(5, 3))), grid.bounds());
+        assert_eq!(2, grid.len());
+    }
+
+    #[test]
+    fn test_insert_and_entry() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(None, grid.insert(Pos::new(1, 1), 1));
+        assert_eq!(Some(1), grid.insert(Pos::new(1, 1), 2));
+        *grid.entry(Pos::new(-1, 0)).or_insert(0) += 5;
+        assert_eq!(Some(&5), grid.get(Pos::new(-1, 0)));
+        assert_eq!(Some((Pos::new(-1, 0), Pos::new(1, 1))), grid.bounds());
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = sample();
+        let cell = |c: Option<&char>| c.copied().unwrap_or('.');
+        assert_eq!("a...\n....\n...b", grid.render(None, cell));
+        assert_eq!("..O.\na...\n....\n...b", grid.render(Some('O'), cell));
+
+        let empty = SparseGrid::<char>::new();
+        assert_eq!("", empty.render(None, cell));
+        assert_eq!("O", empty.render(Some('O'), cell));
+    }
+
+    #[test]
+    fn test_to_grid() {
+        let grid = sample().to_grid('.');
+        assert_eq!("a...\n....\n...b", grid.to_string());
+
+        let empty = SparseGrid::<char>::new().to_grid('.');
+        assert_eq!((0, 0), (empty.width(), empty.height()));
+    }
+}
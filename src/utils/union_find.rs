@@ -0,0 +1,254 @@
+#![allow(unused)]
+
+// Disjoint sets, for grouping things that are connected in pairs. DisjointSets works on
+// the indices 0..n, UnionFind puts an interner in front of it for any hashable key. Finds
+// compress the path to the root and unions hang the smaller tree under the larger, so
+// every operation is close to constant time.
+
+use std::{borrow::Borrow, hash::Hash};
+
+use super::interner::Interner;
+
+#[derive(Debug, Clone, Default)]
+pub struct DisjointSets {
+    parent: Vec<usize>,
+    // Only up to date for roots
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSets {
+    // Every element starts in a set of its own
+    pub fn new(len: usize) -> DisjointSets {
+        DisjointSets {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    // Adds an element in a set of its own and returns its index
+    pub fn add(&mut self) -> usize {
+        let element = self.parent.len();
+        self.parent.push(element);
+        self.size.push(1);
+        self.components += 1;
+        element
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // The root of the element's set
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut current = element;
+        while self.parent[current] != root {
+            current = std::mem::replace(&mut self.parent[current], root);
+        }
+        root
+    }
+
+    // Whether the two were in different sets before
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (large, small) = if self.size[a] < self.size[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    // The size of the set the element is in
+    pub fn size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.size[root]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    // Every set with its elements in increasing order, the sets ordered by their first
+    // element
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = vec![None; self.len()];
+        let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for element in 0..self.len() {
+            let root = self.find(element);
+            let index = *index_of_root[root].get_or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[index].push(element);
+        }
+        components
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnionFind<K> {
+    keys: Interner<K>,
+    sets: DisjointSets,
+}
+
+impl<K> Default for UnionFind<K> {
+    fn default() -> UnionFind<K> {
+        UnionFind {
+            keys: Interner::default(),
+            sets: DisjointSets::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> UnionFind<K> {
+    pub fn new() -> UnionFind<K> {
+        UnionFind::default()
+    }
+
+    // Adds the key in a set of its own, if it isn't in one already
+    pub fn insert(&mut self, key: K) -> usize {
+        let id = self.keys.intern(key);
+        if id == self.sets.len() {
+            self.sets.add();
+        }
+        id
+    }
+
+    // Inserts keys that weren't seen before. Returns whether the two were in different
+    // sets.
+    pub fn union(&mut self, a: K, b: K) -> bool {
+        let (a, b) = (self.insert(a), self.insert(b));
+        self.sets.union(a, b)
+    }
+
+    // The key that stands for the whole set, None for keys that were never inserted
+    pub fn find<Q>(&mut self, key: &Q) -> Option<&K>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let id = self.keys.id(key)?;
+        Some(self.keys.key(self.sets.find(id)))
+    }
+
+    pub fn connected<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match (self.keys.id(a), self.keys.id(b)) {
+            (Some(a), Some(b)) => self.sets.connected(a, b),
+            _ => false,
+        }
+    }
+
+    // The size of the key's set, 0 for keys that were never inserted
+    pub fn size<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.keys.id(key).map_or(0, |id| self.sets.size(id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.sets.component_count()
+    }
+
+    // Every set with its keys in the order they were inserted
+    pub fn components(&mut self) -> impl Iterator<Item = Vec<&K>> {
+        let keys = &self.keys;
+        self.sets
+            .components()
+            .into_iter()
+            .map(move |component| component.into_iter().map(|id| keys.key(id)).collect())
+    }
+}
+
+#[cfg(test)]
+mod test_union_find {
+    use super::*;
+
+    #[test]
+    fn test_disjoint_sets() {
+        let mut sets = DisjointSets::new(6);
+        assert_eq!(6, sets.component_count());
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(4, sets.size(2));
+        assert_eq!(3, sets.component_count());
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![4], vec![5]], sets.components());
+
+        let added = sets.add();
+        sets.union(5, added);
+        assert_eq!(
+            vec![vec![0, 1, 2, 3], vec![4], vec![5, 6]],
+            sets.components()
+        );
+    }
+
+    #[test]
+    fn test_long_chain_is_compressed() {
+        // Union by size keeps the trees flat, and a find flattens what is left
+        let mut sets = DisjointSets::new(1000);
+        for i in 1..1000 {
+            sets.union(i - 1, i);
+        }
+        let root = sets.find(999);
+        assert!((0..1000).all(|i| sets.parent[i] == root));
+        assert_eq!(1, sets.component_count());
+    }
+
+    #[test]
+    fn test_union_find_keys() {
+        let mut network = UnionFind::new();
+        network.union("kh", "tc");
+        network.union("qp", "kh");
+        network.union("de", "cg");
+        network.insert("ka");
+        assert!(network.connected("qp", "tc"));
+        assert!(!network.connected("qp", "de"));
+        assert!(!network.connected("qp", "zz"));
+        assert_eq!(3, network.size("tc"));
+        assert_eq!(0, network.size("zz"));
+        assert_eq!(None, network.find("zz"));
+        assert_eq!(network.find("kh").copied(), network.find("qp").copied());
+        assert_eq!(3, network.component_count());
+        assert_eq!(
+            vec![vec![&"kh", &"tc", &"qp"], vec![&"de", &"cg"], vec![&"ka"]],
+            network.components().collect::<Vec<_>>()
+        );
+    }
+}